@@ -4,23 +4,43 @@
  * Date: 05.02.2023
  * */
 
+mod bitboard;
+mod castling;
+mod chess960;
 mod chess_move;
 mod color;
+mod draw;
+mod evaluation;
+mod fen;
+mod game_result;
+mod history;
+mod movegen;
+mod perft;
 mod piece;
+mod render;
+mod san;
+mod search;
+mod transposition;
+mod zobrist;
 
 //use crate::board::color::{BoardColor, Color};
-pub use chess_move::{Move, MoveErr, MoveType};
+use bitboard::Bitboards;
+pub use castling::CastlingRights;
+pub use chess960::CHESS960_POSITIONS;
+pub use chess_move::{parse_square, square_name, Move, MoveErr, MoveType};
 use color::*;
-use piece::*;
-
+pub use color::{themes as board_themes, BColor, BTheme, Color};
+pub use game_result::GameResult;
+use history::HistoryEntry;
+pub use piece::{themes as piece_themes, Glyphs, Theme as PieceTheme};
+pub use render::{ColorMode, Renderer, StringRenderer};
+pub use search::{SearchLimits, SearchResult, MAX_DEPTH};
+pub use transposition::{TranspositionTable, DEFAULT_HASH_MB, MAX_HASH_MB};
+
+// Defaults, see themes.rs for choosing others by name
 const PIECE_SET: piece::Theme = piece::themes::CHALLENGER; // Or you can type (u8, u8, u8), (u8, u8, u8) instead
 const BOARD_THEME: BTheme = color::themes::RUST;
 
-static mut LAST_MOVE: ([usize; 2], [usize; 2]) = ([1, 2], [1, 2]);
-static mut ESCAPE: &str = "\x1b[0m";
-
-#[allow(non_upper_case_globals)]
-const colored: bool = true; // will map it to command line argument thats why it is not SCREAMIN'
 const PROMOTED: [char; 4] = ['q', 'r', 'b', 'n'];
 const DEFAULT_PIECE_NOTATION: &str =
     "rnbqkbnrpppppppp                                PPPPPPPPRNBQKBNR"; // I have chosen to use something called FEN to encode FEN into board. This is fixed sized.
@@ -28,8 +48,7 @@ const BOUNDS: [usize; 2] = [1, 8]; // Bounds of the board
                                    /* FEN starts from left upper corner of the board and then all the way down to right bottom.
                                     * Lover-case characters (rnbqkp) represent black while upper-cases (RNBQKB) represent white FEN*/
 
-#[allow(dead_code)] // can be used later
-fn nums_to_whitespaces(lit: &String) -> String {
+fn nums_to_whitespaces(lit: &str) -> String {
     let mut s = String::with_capacity(lit.len());
     lit.chars().for_each(|ch| {
         if let Ok(num) = ch.to_string().parse::<u8>() {
@@ -41,16 +60,42 @@ fn nums_to_whitespaces(lit: &String) -> String {
     s
 }
 
+// Name of the piece in error messages
+fn piece_name(piece: char) -> &'static str {
+    match piece.to_ascii_lowercase() {
+        'p' => "Pawn",
+        'n' => "Knight",
+        'b' => "Bishop",
+        'r' => "Rook",
+        'q' => "Queen",
+        _ => "King",
+    }
+}
+
 #[allow(non_snake_case, dead_code)]
 #[derive(Debug, Clone, PartialEq)]
 pub struct Board {
-    pub color: BoardColor,         // Used for storing the color of the board
-    pub board: [[char; 8]; 8],     // Used for storing the board TODO
-    pub FEN: String,               // Used for storing the FEN -> 72 is the max length of FEN
-    pub turn: bool,                // White or black turn
-    pub coordinates: bool,         // Used for displaying the coordinates
-    pub white_color: piece::Color, // Used for storing the color of the white pieces
-    pub black_color: piece::Color, // Used for storing the color of the black pieces
+    pub color: BoardColor,              // Used for storing the color of the board
+    pub board: [[char; 8]; 8],          // View of the bitboards, used for drawing the board
+    pub bitboards: Bitboards,           // Pieces as 64-bit sets, see bitboard.rs
+    pub hash: u64,                      // Zobrist key of the position, see zobrist.rs
+    pub FEN: String,                    // Used for storing the FEN -> 72 is the max length of FEN
+    pub turn: bool,                     // White or black turn
+    pub coordinates: bool,              // Used for displaying the coordinates
+    pub colors: ColorMode,              // Colors the terminal can show, --no-color turns them off
+    pub flipped: bool,                  // Drawing black at the bottom
+    pub auto_flip: bool,                // Side to move at the bottom in games between two players
+    pub glyphs: Glyphs,                 // Letters, chess symbols or icons for the pieces
+    pub wide: bool,                     // Bigger squares which look square in the terminal
+    pub white_color: piece::Color,      // Used for storing the color of the white pieces
+    pub black_color: piece::Color,      // Used for storing the color of the black pieces
+    pub en_passant: Option<[usize; 2]>, // Square behind a pawn which just moved two squares
+    pub castling: CastlingRights,       // Which sides can still castle (KQkq)
+    pub chess960: bool,                 // Castling is written as the king taking its own rook
+    pub halfmove_clock: u32,            // Moves since the last capture or pawn move
+    pub fullmove_number: u32,           // Starts from 1 and increases after black moves
+    pub history: Vec<HistoryEntry>,     // Played moves, last one is on top
+    pub redo: Vec<HistoryEntry>,        // Undone moves which can be played again
 }
 
 impl Board {
@@ -58,40 +103,19 @@ impl Board {
     pub fn new(fen: String) -> Board {
         // Empty board
         Board {
-            color: BOARD_THEME.into(),
             board: [[' '; 8]; 8],
+            bitboards: Bitboards::default(),
+            hash: 0,
             FEN: fen,
-            turn: true,
-            coordinates: true,
-            white_color: Default::default(),
-            black_color: Default::default(),
+            ..Default::default()
         }
     }
 
-    #[allow(dead_code)]
-    pub fn from_fen(fen: String) -> Board {
-        let mut tmp = Board {
-            color: BOARD_THEME.into(),
-            board: [[' '; 8]; 8],
-            FEN: fen,
-            turn: true,
-            coordinates: true,
-            white_color: Default::default(),
-            black_color: Default::default(),
-        };
-        tmp.decode();
-        tmp
-    }
     #[allow(dead_code)]
     pub fn from_vec(board: [[char; 8]; 8]) -> Board {
         let mut tmp = Board {
-            color: BOARD_THEME.into(),
             board,
-            FEN: String::new(),
-            turn: true,
-            coordinates: true,
-            white_color: Default::default(),
-            black_color: Default::default(),
+            ..Default::default()
         };
         tmp.encode();
         tmp
@@ -104,7 +128,23 @@ impl Board {
 
     #[allow(dead_code)]
     pub fn classic(&mut self) -> Board {
-        Board::default()
+        let mut board = Board::default();
+        board.copy_style(self);
+        board
+    }
+
+    /// Takes how the other board is drawn (colors, flipping, coordinates),
+    /// used when a new position replaces the board in the game.
+    pub fn copy_style(&mut self, other: &Board) {
+        self.color = other.color;
+        self.white_color = other.white_color;
+        self.black_color = other.black_color;
+        self.colors = other.colors;
+        self.flipped = other.flipped;
+        self.auto_flip = other.auto_flip;
+        self.glyphs = other.glyphs;
+        self.wide = other.wide;
+        self.coordinates = other.coordinates;
     }
 
     #[allow(dead_code)]
@@ -127,6 +167,7 @@ impl Board {
     #[allow(dead_code)]
     pub fn reverse_turn(&mut self) {
         self.turn = !self.turn;
+        self.hash = self.compute_hash();
     }
 
     #[allow(dead_code)]
@@ -143,19 +184,10 @@ impl Board {
 
         self.board[to[1] - 1][to[0] - 1] = self.board[from[1] - 1][from[0] - 1];
         self.board[from[1] - 1][from[0] - 1] = ' ';
+        self.encode();
     }
 
-    // TODO undo does not replace the taken piece
     pub fn move_piece(&mut self, current_move: Move) -> Result<MoveType, MoveErr> {
-        let result = current_move.validate_move(&self.FEN, self.turn);
-
-        if let Err(MoveErr(err)) = result {
-            // for now I am ignoring
-            return Err(MoveErr(err)); // TODO improve these to MoveErr
-        }
-
-        let result = result.unwrap();
-
         let (from, to) = current_move.decode_move();
         if from == to {
             return Err(MoveErr(
@@ -172,245 +204,185 @@ impl Board {
                 return Err(MoveErr("Invalid coordinates".to_owned()));
             }
         }
-        // TODO
-        //        self.board[to.0 as usize][to.1 as usize] = self.board[from.0 as usize][from.1 as usize];
-        //        self.board[from.0 as usize][from.1 as usize] = 0;
-        //        TODO Clean this shit. It's a mess
-        if self.board[from[1] - 1_usize][from[0] - 1_usize] == ' '
-            || (self.board[from[1] - 1_usize][from[0] - 1_usize]
-                .to_lowercase()
-                .collect::<Vec<char>>()[0]
-                != self.board[from[1] - 1_usize][from[0] - 1_usize]
-                && self.turn == false)
-            || (self.board[from[1] - 1_usize][from[0] - 1_usize]
-                .to_lowercase()
-                .collect::<Vec<char>>()[0]
-                == self.board[from[1] - 1_usize][from[0] - 1_usize]
-                && self.turn == true)
-        {
-            // If the piece is empty or if the piece is black and it's white's turn or if the piece is white and it's black's turn
-            return Err(MoveErr("Wait for your turn".to_owned())); // TODO! Doesn't work as it supposed to do
-        }
 
-        /* Castling involves 2 moves at a time,
-         * This is against my programmes architechture,
-         * This is why below function is ated in this file
-         * * * * * * * * * * * * * * * * * * * * * * * * */
-
-        if result == MoveType::Castle {
-            /* Make sure that squares are empty! */
-            // This will move rook
-            //self.reverse_turn();
-            /* Go to the corner which King has been moved */
-            //self.encode();
-            match self.turn {
-                true => {
-                    // White
-                    // TODO Make this for the last ranks: 1, 8
-                    if dbg!(current_move.decode_move().1[0]) == 7 {
-                        // if we are trying to castle to the right
-                        self.move_unchecked(&Move::new([8, 8], [6, 8]));
-                    } else if dbg!(current_move.decode_move().1[0]) == 3 {
-                        self.move_unchecked(&Move::new([1, 8], [4, 8]));
-                    }
-                }
+        let piece = self.board[from[1] - 1][from[0] - 1];
+        if piece == ' ' || chess_move::is_white(piece) != self.turn {
+            // If the square is empty or the piece belongs to the opponent
+            return Err(MoveErr("Wait for your turn".to_owned()));
+        }
 
-                false => {
-                    // Black
-                    if dbg!(current_move.decode_move().1[0]) == 7 {
-                        // if we are trying to castle to the right
-                        self.move_unchecked(&Move::new([8, 1], [6, 1]));
-                    } else if dbg!(current_move.decode_move().1[0]) == 3 {
-                        self.move_unchecked(&Move::new([1, 1], [4, 1]));
-                    }
-                }
-            };
+        // A pawn reaching the last row becomes a queen unless the move asks for another piece
+        let promotes = piece.eq_ignore_ascii_case(&'p') && (to[1] == 1 || to[1] == 8);
+        let current_move = match current_move.promotion() {
+            None if promotes => current_move.with_promotion('q'),
+            Some(_) if !promotes => {
+                return Err(MoveErr(
+                    "Only a pawn on the last row can promote".to_owned(),
+                ))
+            }
+            Some(promoted) if !PROMOTED.contains(&promoted) => {
+                return Err(MoveErr(
+                    "A pawn can only promote to a queen, rook, bishop or knight".to_owned(),
+                ))
+            }
+            _ => current_move,
+        };
 
-            self.encode();
-            self.move_unchecked(&current_move);
-            self.decode();
+        if !self.legal_moves().contains(&current_move) {
+            if let Some(king_side) = self.castling_side(&current_move) {
+                // Trying to castle, tell what is stopping it
+                let reason = self.castling_error(king_side);
+                return Err(MoveErr(reason.unwrap_or("Invalid castling").to_owned()));
+            }
+            // A move the piece can make is illegal only if it exposes the king
+            if self.pseudo_legal_moves().contains(&current_move) {
+                return Err(MoveErr("You can't leave your king in check".to_owned()));
+            }
+            return Err(MoveErr(format!("Invalid {} move", piece_name(piece))));
         }
 
-        let factor: i8 = match self.turn {
-            true => -1,
-            false => 1,
+        self.redo.clear(); // A new move makes the undone moves meaningless
+        Ok(self.make_move(&current_move))
+    }
+
+    /* Plays a move without validating it.
+     * Callers have to make sure that the move is in legal_moves(),
+     * a promotion without a piece makes a queen */
+    pub(crate) fn make_move(&mut self, current_move: &Move) -> MoveType {
+        let (from, to) = current_move.decode_move();
+        let piece = self.board[from[1] - 1][from[0] - 1];
+        let castling = self.castling_side(current_move);
+        let taken = match castling {
+            Some(_) => ' ', // The king only "takes" its own rook in Chess960
+            None => self.board[to[1] - 1][to[0] - 1],
+        };
+        let last_row = if self.turn { 1 } else { 8 };
+        // Keys of the things that change without set_square
+        let old_keys = zobrist::castling_key(&self.castling) ^ self.en_passant_key();
+
+        let move_type = match piece.to_ascii_lowercase() {
+            'p' if to[1] == last_row => MoveType::Promotion,
+            'p' if from[1].abs_diff(to[1]) == 2 => MoveType::DoublePawn,
+            'p' if Some(to) == self.en_passant => MoveType::EnPassant,
+            'p' if taken != ' ' => MoveType::PawnCapture,
+            'k' if castling.is_some() => MoveType::Castle,
+            _ if taken != ' ' => MoveType::Capture,
+            _ => MoveType::Regular,
         };
 
-        if result == MoveType::EnPassant {
-            /*
-             * This function is the seconds pass for en passand move type validation,
-             * This will clearify if the taken pawn has been moved one move ago or not.
-             * and this function will clear the take piece becuase en passand does not
-             * replace the piece it takes.
-             * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
-
-            let taken_pawn = current_move.decode_move().1;
-            let taken_pawn = [
-                taken_pawn[0],
-                (taken_pawn[1] as i8 + (1 * -factor)) as usize,
-            ];
-
-            // Verifying if the taken pawn has been moved one move ago
-            unsafe {
-                if LAST_MOVE.1 != taken_pawn {
-                    return Err(MoveErr("Invalid en passant move".to_owned()));
-                }
-            }
-            // Clearing the taken pawn
-            self.remove(taken_pawn);
+        // Saving everything this move destroys so it can be undone
+        self.history.push(HistoryEntry {
+            played: current_move.clone(),
+            move_type: move_type.clone(),
+            moved: piece,
+            taken: match move_type {
+                MoveType::EnPassant => self.board[from[1] - 1][to[0] - 1],
+                _ => taken,
+            },
+            castling: self.castling,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            hash: self.hash,
+        });
+
+        if move_type == MoveType::EnPassant {
+            // En passant does not replace the piece it takes
+            self.set_square([to[0], from[1]], ' ');
         }
 
-        unsafe {
-            LAST_MOVE = current_move.decode_move(); // Saving the last move
+        // Moving the king or a rook, or taking a rook loses the castling rights
+        if piece.eq_ignore_ascii_case(&'k') {
+            self.castling.remove_side(self.turn);
         }
+        self.castling.touch(from);
+        self.castling.touch(to);
 
-        self.board[to[1] - 1_usize][to[0] - 1_usize] =
-            self.board[from[1] - 1_usize][from[0] - 1_usize]; // Taking place of the piece
-        self.turn = !self.turn; // Changing the turn
-        self.board[from[1] - 1_usize][from[0] - 1_usize] = ' '; // Leaving moved piece's place empty
-        return Ok(result);
-    }
+        // Remembering the skipped square so the opponent can take en passant
+        self.en_passant = match move_type {
+            MoveType::DoublePawn => Some([from[0], (from[1] + to[1]) / 2]),
+            _ => None,
+        };
 
-    pub fn undo_move(&mut self) {
-        unsafe {
-            self.board[LAST_MOVE.0[1] - 1_usize][LAST_MOVE.0[0] - 1_usize] =
-                self.board[LAST_MOVE.1[1] - 1_usize][LAST_MOVE.1[0] - 1_usize];
-            self.turn = !self.turn;
-            self.board[LAST_MOVE.1[1] - 1_usize][LAST_MOVE.1[0] - 1_usize] = ' ';
+        // Pawn moves and captures can't be taken back, so they reset the clock
+        self.halfmove_clock = match piece.eq_ignore_ascii_case(&'p') || taken != ' ' {
+            true => 0,
+            false => self.halfmove_clock + 1,
+        };
+        if !self.turn {
+            self.fullmove_number += 1;
         }
-        self.encode();
-    }
 
-    pub fn draw_ascii(&mut self) {
-        self.encode(); // Encode the board into FEN -> Because it's easier to display
-                       // TODO:
-                       // 1. Make this function flexible to draw any board
-                       // 2. Add some piece sets to choose from
-        const FRAME_HOR: &str = "";
-        const FRAME_VER: &str = ""; // Right Top Left Bottom
-        const MARGIN: &str = " ";
-        let size = 40;
-        // Drawing board to the screen
-        /* This is just experimental. I will convert this method to use OpenGL */
-        let fen = &self.FEN;
-        let mut bg_color;
-        let mut fg_color;
-
-        let mut row = 0;
-        let mut column = 0;
-        println!("{}", FRAME_HOR.repeat(size));
-        for piece in fen.chars() {
-            print!("{FRAME_VER}");
-            if colored {
-                if piece.is_lowercase() {
-                    fg_color = Into::<color::Color>::into(self.black_color).foreground();
-                } else {
-                    fg_color = Into::<color::Color>::into(self.white_color).foreground();
-                }
-                if (row + column / 8) % 2 == 0 {
-                    bg_color = self.color.rgb().0.background();
-                } else {
-                    bg_color = self.color.rgb().1.background();
+        let placed = match (&move_type, current_move.promotion()) {
+            (MoveType::Promotion, promoted) => {
+                let promoted = promoted.unwrap_or('q');
+                match self.turn {
+                    true => promoted.to_ascii_uppercase(),
+                    false => promoted,
                 }
-            } else {
-                unsafe {
-                    ESCAPE = "";
-                }
-                bg_color = "".to_owned();
-                fg_color = "".to_owned();
             }
-            column += 1; // Incrementing column
-
-            match piece {
-                // Unsafe blocks are needed because of use of global variables
-                ' ' => unsafe { print!("{bg_color}{MARGIN}{fg_color} {MARGIN}{ESCAPE}") },
-                'r' => unsafe {
-                    print!(
-                        "{bg_color}{MARGIN}{fg_color}{piece}{MARGIN}{ESCAPE}",
-                        piece = Black::Rook
-                    )
-                }, //♜
-                'n' => unsafe {
-                    print!(
-                        "{bg_color}{MARGIN}{fg_color}{piece}{MARGIN}{ESCAPE}",
-                        piece = Black::Knight
-                    )
-                }, //♞
-                'b' => unsafe {
-                    print!(
-                        "{bg_color}{MARGIN}{fg_color}{piece}{MARGIN}{ESCAPE}",
-                        piece = Black::Bishop
-                    )
-                }, //♝
-                'q' => unsafe {
-                    print!(
-                        "{bg_color}{MARGIN}{fg_color}{piece}{MARGIN}{ESCAPE}",
-                        piece = Black::Queen
-                    )
-                }, //♛
-                'k' => unsafe {
-                    print!(
-                        "{bg_color}{MARGIN}{fg_color}{piece}{MARGIN}{ESCAPE}",
-                        piece = Black::King
-                    )
-                }, //♚
-                'p' => unsafe {
-                    print!(
-                        "{bg_color}{MARGIN}{fg_color}{piece}{MARGIN}{ESCAPE}",
-                        piece = Black::Pawn
-                    )
-                }, //♟
-                'R' => unsafe {
-                    print!(
-                        "{bg_color}{MARGIN}{fg_color}{piece}{MARGIN}{ESCAPE}",
-                        piece = White::Rook
-                    )
-                }, //♖
-                'N' => unsafe {
-                    print!(
-                        "{bg_color}{MARGIN}{fg_color}{piece}{MARGIN}{ESCAPE}",
-                        piece = White::Knight
-                    )
-                }, //♘
-                'B' => unsafe {
-                    print!(
-                        "{bg_color}{MARGIN}{fg_color}{piece}{MARGIN}{ESCAPE}",
-                        piece = White::Bishop
-                    )
-                }, //♗
-                'Q' => unsafe {
-                    print!(
-                        "{bg_color}{MARGIN}{fg_color}{piece}{MARGIN}{ESCAPE}",
-                        piece = White::Queen
-                    )
-                }, //♕
-                'K' => unsafe {
-                    print!(
-                        "{bg_color}{MARGIN}{fg_color}{piece}{MARGIN}{ESCAPE}",
-                        piece = White::King
-                    )
-                }, //♔
-                'P' => unsafe {
-                    print!(
-                        "{bg_color}{MARGIN}{fg_color}{piece}{MARGIN}{ESCAPE}",
-                        piece = White::Pawn
-                    )
-                }, //♙
-                _ => panic!("Invalid FEN"),
+            _ => piece,
+        };
+        match castling {
+            Some(king_side) => {
+                /* Castling involves 2 moves at a time, the rook jumps to the
+                 * other side of the king. In Chess960 they can land on each
+                 * other's squares so both of them are lifted first */
+                let (rook_from, king_to, rook_to) = self.castling.squares(self.turn, king_side);
+                let rook = self.board[rook_from[1] - 1][rook_from[0] - 1];
+                self.set_square(from, ' ');
+                self.set_square(rook_from, ' ');
+                self.set_square(king_to, piece);
+                self.set_square(rook_to, rook);
             }
-            print!("{FRAME_VER}");
-
-            row += 1;
-            if row % 8 == 0 {
-                print!("{}\n", FRAME_HOR.repeat(size));
-                row = 0;
+            None => {
+                self.set_square(to, placed); // Taking place of the piece
+                self.set_square(from, ' '); // Leaving moved piece's place empty
             }
         }
-        println!("\n\nFEN: {}", fen);
+        self.turn = !self.turn; // Changing the turn
+        self.hash ^= old_keys
+            ^ zobrist::castling_key(&self.castling)
+            ^ self.en_passant_key()
+            ^ zobrist::SIDE;
+        move_type
+    }
+
+    /// Returns true if the king of the side to move is attacked.
+    pub fn is_check(&self) -> bool {
+        match self.bitboards.king(self.turn) {
+            Some(king) => self.bitboards.is_attacked(king, !self.turn),
+            None => false,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn is_checkmate(&self) -> bool {
+        self.is_check() && self.legal_moves().is_empty()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_stalemate(&self) -> bool {
+        !self.is_check() && self.legal_moves().is_empty()
+    }
+
+    /// Returns the result if the game is over, None if it still goes on.
+    /// Claimable draws don't end the game, see claimable_draw for them.
+    pub fn result(&self) -> Option<GameResult> {
+        if !self.legal_moves().is_empty() {
+            // Mates come first, a mate on the last move counts even if a draw rule is reached
+            return self.automatic_draw();
+        }
+        match self.is_check() {
+            true => Some(GameResult::Checkmate(!self.turn)), // The side which gave the mate wins
+            false => Some(GameResult::Stalemate),
+        }
     }
 
     pub fn encode(&mut self) {
-        // Will convert board to FEN
+        // Will convert board to FEN, call it after changing the board by hand
+        self.bitboards = Bitboards::from_grid(&self.board);
+        self.hash = self.compute_hash();
         let mut tmp = String::with_capacity(72);
         for i in 0..self.board.len() {
             for j in 0..self.board[i].len() {
@@ -422,11 +394,22 @@ impl Board {
 
     pub fn decode(&mut self) {
         // Will convert FEN to board
-        for i in 0..self.board.len() {
-            for j in 0..self.board[i].len() {
-                self.board[i][j] = self.FEN.chars().nth(i * 8 + j).expect("Invalid FEN");
-            }
+        let mut pieces = self.FEN.chars();
+        for square in self.board.iter_mut().flatten() {
+            *square = pieces.next().expect("Invalid FEN");
         }
+        self.bitboards = Bitboards::from_grid(&self.board);
+        self.hash = self.compute_hash();
+    }
+
+    // Puts the piece (or ' ') on the square, keeping the bitboards in sync with the board
+    fn set_square(&mut self, square: [usize; 2], piece: char) {
+        let index = bitboard::index(square);
+        let old = self.board[square[1] - 1][square[0] - 1];
+        self.bitboards.remove(index, old);
+        self.bitboards.put(index, piece);
+        self.hash ^= zobrist::piece_key(old, index) ^ zobrist::piece_key(piece, index);
+        self.board[square[1] - 1][square[0] - 1] = piece;
     }
 
     pub fn simple_evaluate(&self) -> i32 {
@@ -453,36 +436,18 @@ impl Board {
     }
 
     pub fn evaluate(&self) -> i32 {
-        // Will evaluate the board (with a depth search)
-        todo!();
-    }
-
-    pub fn promote_piece(&mut self, piece: char) -> Result<(), ()> {
-        /*
-         * Because this function has to get called after the piece moved,
-         * We have to take !turn to get the moved piece's color
-         * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
-        let turn = !self.turn;
-        let coords: [usize; 2];
-        unsafe {
-            coords = LAST_MOVE.1;
+        // Evaluates the board with a depth search, positive is good for white
+        let score = self.search(SearchLimits::default()).score;
+        match self.turn {
+            true => score,
+            false => -score,
         }
-        let piece = piece.to_ascii_lowercase();
-        for i in PROMOTED {
-            if i == piece {
-                match turn {
-                    true => self.board[coords[0] - 1][coords[1] - 1] = piece.to_ascii_uppercase(),
-                    _ => self.board[coords[0] - 1][coords[1] - 1] = piece,
-                }
-                return Ok(());
-            }
-        }
-        Err(())
     }
 
     #[allow(dead_code)]
     pub fn change_piece(&mut self, coords: [usize; 2], piece: char) {
         self.board[coords[0]][coords[1]] = piece;
+        self.encode();
     }
 
     //pub fn get_coordinate(&self, x: usize, y: usize) -> White {
@@ -495,11 +460,25 @@ impl Default for Board {
         let mut init = Board {
             color: BOARD_THEME.into(),
             board: [[' '; 8]; 8],
+            bitboards: Bitboards::default(),
+            hash: 0,
             FEN: String::from(DEFAULT_PIECE_NOTATION),
             turn: true, // white starts the game
             coordinates: true,
+            colors: ColorMode::Truecolor,
+            flipped: false,
+            auto_flip: false,
+            glyphs: Glyphs::Ascii,
+            wide: false,
             white_color: PIECE_SET.0,
             black_color: PIECE_SET.1,
+            en_passant: None,
+            castling: CastlingRights::default(),
+            chess960: false,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            history: Vec::new(),
+            redo: Vec::new(),
         };
         init.decode();
         init
@@ -522,7 +501,7 @@ mod tests {
     fn test_board_from_fen() {
         let mut board = Board::default();
         board.encode();
-        let mut board2 = Board::from_fen(board.FEN.clone());
+        let mut board2 = Board::from_fen(&board.to_fen()).unwrap();
         board2.encode();
         assert_eq!(board, board2);
     }
@@ -531,7 +510,7 @@ mod tests {
     fn test_board_from_vec() {
         let mut board = Board::default();
         board.encode();
-        let mut board2 = Board::from_vec(board.board.clone());
+        let mut board2 = Board::from_vec(board.board);
         board2.encode();
         assert_eq!(board, board2);
     }
@@ -0,0 +1,397 @@
+/*
+ * File: bitboard.rs
+ * Purpose: Keeping the pieces as 64-bit sets and looking up their attacks
+ * Date: 18.10.2026
+ * */
+
+/* A bitboard is a u64 where every bit is a square. Bits are in the same
+ * order as the grid and the FEN: bit 0 is a8, bit 7 is h8, bit 56 is a1 and
+ * bit 63 is h1. So the square [x, y] of a Move is bit (y - 1) * 8 + (x - 1)
+ * and going one row towards the 1st rank is shifting 8 bits to the left.
+ *
+ * Board keeps a bitboard for every piece type of both colors. Knight, king
+ * and pawn attacks don't depend on other pieces, they are read from tables
+ * built at compile time. Rook and bishop attacks stop at the first piece in
+ * the way, they are found with magic bitboards:
+ *
+ *  slot = ((occupied & mask) * magic) >> shift
+ *
+ * mask holds the squares which can block the slider from that square and
+ * the magic number sends every combination of blockers to its own slot of
+ * the table (or to a slot with the same attacks). The magic numbers were
+ * found by trying sparse random numbers until one had no collisions, the
+ * tables are filled the first time a slider looks something up.
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use super::chess_move::is_white;
+use super::movegen::{Grid, BISHOP_DIRECTIONS, KING_OFFSETS, KNIGHT_OFFSETS, ROOK_DIRECTIONS};
+use lazy_static::lazy_static;
+
+pub type Bitboard = u64;
+
+pub const PAWN: usize = 0;
+pub const KNIGHT: usize = 1;
+pub const BISHOP: usize = 2;
+pub const ROOK: usize = 3;
+pub const QUEEN: usize = 4;
+pub const KING: usize = 5;
+const PIECES: [char; 6] = ['p', 'n', 'b', 'r', 'q', 'k'];
+
+pub const LIGHT_SQUARES: Bitboard = 0xAA55_AA55_AA55_AA55; // a8 is a light square
+pub const KNIGHT_ATTACKS: [Bitboard; 64] = step_attacks(&KNIGHT_OFFSETS);
+pub const KING_ATTACKS: [Bitboard; 64] = step_attacks(&KING_OFFSETS);
+pub const PAWN_ATTACKS: [[Bitboard; 64]; 2] = [
+    step_attacks(&[(-1, -1), (1, -1)]), // White pawns take towards the 8th rank
+    step_attacks(&[(-1, 1), (1, 1)]),
+];
+
+/// Bit of the square [x, y].
+pub fn index(square: [usize; 2]) -> usize {
+    (square[1] - 1) * 8 + square[0] - 1
+}
+
+/// Square [x, y] of the bit.
+pub fn square(index: usize) -> [usize; 2] {
+    [index % 8 + 1, index / 8 + 1]
+}
+
+pub fn bit(index: usize) -> Bitboard {
+    1 << index
+}
+
+// Index of the color in the tables, white comes first
+pub fn side(white: bool) -> usize {
+    usize::from(!white)
+}
+
+pub(super) fn kind(piece: char) -> Option<usize> {
+    PIECES
+        .iter()
+        .position(|&kind| kind == piece.to_ascii_lowercase())
+}
+
+/// Bits of the bitboard from the lowest, so squares come in the grid order.
+pub struct Squares(Bitboard);
+
+impl Iterator for Squares {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.0 == 0 {
+            return None;
+        }
+        let index = self.0.trailing_zeros() as usize;
+        self.0 &= self.0 - 1; // Clearing the lowest bit
+        Some(index)
+    }
+}
+
+pub fn squares(bitboard: Bitboard) -> Squares {
+    Squares(bitboard)
+}
+
+const fn step_attacks(offsets: &[(i8, i8)]) -> [Bitboard; 64] {
+    let mut table = [0; 64];
+    let mut from = 0;
+    while from < 64 {
+        let mut i = 0;
+        while i < offsets.len() {
+            let x = (from % 8) as i8 + offsets[i].0;
+            let y = (from / 8) as i8 + offsets[i].1;
+            if x >= 0 && x < 8 && y >= 0 && y < 8 {
+                table[from] |= 1 << (y * 8 + x);
+            }
+            i += 1;
+        }
+        from += 1;
+    }
+    table
+}
+
+// Walks every direction until a piece is hit, slow but easy to trust
+fn slide(from: usize, occupied: Bitboard, directions: &[(i8, i8)]) -> Bitboard {
+    let mut attacks = 0;
+    for (dx, dy) in directions {
+        let (mut x, mut y) = ((from % 8) as i8 + dx, (from / 8) as i8 + dy);
+        while (0..8).contains(&x) && (0..8).contains(&y) {
+            let target = bit((y * 8 + x) as usize);
+            attacks |= target;
+            if occupied & target != 0 {
+                break;
+            }
+            x += dx;
+            y += dy;
+        }
+    }
+    attacks
+}
+
+// Squares which can block the slider, a piece on the edge blocks nothing
+fn blockers(from: usize, directions: &[(i8, i8)]) -> Bitboard {
+    let mut mask = 0;
+    for (dx, dy) in directions {
+        let (mut x, mut y) = ((from % 8) as i8 + dx, (from / 8) as i8 + dy);
+        while (0..8).contains(&(x + dx)) && (0..8).contains(&(y + dy)) {
+            mask |= bit((y * 8 + x) as usize);
+            x += dx;
+            y += dy;
+        }
+    }
+    mask
+}
+
+#[rustfmt::skip]
+const ROOK_MAGICS: [u64; 64] = [
+    0x1080004008801020, 0x0840092002C03000, 0x1900200010400900, 0x0880100008000480,
+    0x4200100420080200, 0x8100020100080400, 0x0200040110886200, 0x0200008040220411,
+    0x0404800084400220, 0x0000401000402000, 0x0086001081220440, 0x0408800800100280,
+    0x000A001201040820, 0x8848800200840080, 0x4001000100040200, 0x0442000102105084,
+    0x9080010020804100, 0x0040404000201009, 0x0000808010002009, 0x2200090021D00100,
+    0x0008008008040080, 0x0004004002010040, 0x0011040008015042, 0x00000A0001768104,
+    0x0000800080204009, 0x2010004140002001, 0x9800200280100080, 0x1000100080080080,
+    0x0442000A00049020, 0x2100040080020080, 0x0800120400900148, 0x0010040A00128541,
+    0x2800804000800030, 0x1010002000400041, 0x4000200011004100, 0x0610008410800800,
+    0x0400802402800800, 0xC100020080800400, 0x0002000802000401, 0x0182085882000401,
+    0x0220204000808000, 0x2860100040024022, 0x0001002004110040, 0x99101042000A0020,
+    0x0004080004008080, 0x0010040002008080, 0x2012004881020004, 0x8300842444820011,
+    0x0088403882010200, 0x0820400080210100, 0x0110910040A00300, 0x0801100280080480,
+    0x0242009008200600, 0x1002000489500200, 0x0040800200010080, 0x0091800041000080,
+    0x0000209300488001, 0x04C1002414824001, 0x020020000B001041, 0x7000100004200901,
+    0x8002002004100802, 0x30010002084C0007, 0x0888221800813004, 0x4000002840840112,
+];
+
+#[rustfmt::skip]
+const BISHOP_MAGICS: [u64; 64] = [
+    0xA010041108003100, 0x006082020A002900, 0x6810010619200000, 0x08281A0520000408,
+    0x0001104001000400, 0x0018901008048400, 0x00040A0210245280, 0x000200210808A402,
+    0x9140048410821200, 0x0800091010820041, 0x20504804832202C0, 0x0100091401081000,
+    0x8021011140000012, 0x0810020804450400, 0x208B0542109008A2, 0x0080084A08040204,
+    0x0040E2A80811244C, 0x2505022008008108, 0x0430220100420040, 0x010A040420220040,
+    0x1105000290400000, 0x0093001200822120, 0x4000A62048043004, 0x280120048A015004,
+    0x006090002A020814, 0x44042000240800D0, 0x01102800040A4400, 0x1004080080220040,
+    0x0001001011004024, 0x0010044000805040, 0x0914041200820100, 0x0004821012821480,
+    0x0024040500C05021, 0x0088611002080200, 0x0116080A00040020, 0x4000020080080080,
+    0x2450450140840040, 0x0000880201484100, 0x0222020404020092, 0x8081110600002E00,
+    0x2842101105000801, 0x1100809008001025, 0x00020202221C0400, 0x0422014022009020,
+    0x0210046102100C00, 0xC004008082029102, 0x00AA461801101200, 0x0404080080201108,
+    0x020542108C205002, 0x0410544804100100, 0x0040910841100000, 0x0400200042021100,
+    0x00004204850400C0, 0x0200100410A42102, 0x1040020801210102, 0x0805040410420000,
+    0x2884804130100200, 0x800C262201242000, 0x1058000194108800, 0x0014221054420204,
+    0x0104000012A02200, 0x0200881003300100, 0x0140400202840100, 0x0402020801010201,
+];
+
+struct Magic {
+    mask: Bitboard,
+    magic: u64,
+    shift: u32,
+    offset: usize, // Where the attacks of this square start in the table
+}
+
+impl Magic {
+    fn slot(&self, occupied: Bitboard) -> usize {
+        self.offset + ((occupied & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+// Fills the slots of every blocker combination of the square
+fn fill(from: usize, magic: u64, directions: &[(i8, i8)], table: &mut Vec<Bitboard>) -> Magic {
+    let mask = blockers(from, directions);
+    let magic = Magic {
+        mask,
+        magic,
+        shift: 64 - mask.count_ones(),
+        offset: table.len(),
+    };
+    table.resize(table.len() + (1 << mask.count_ones()), 0);
+
+    // Walking every subset of the mask
+    let mut subset: Bitboard = 0;
+    loop {
+        table[magic.slot(subset)] = slide(from, subset, directions);
+        subset = subset.wrapping_sub(mask) & mask;
+        if subset == 0 {
+            break;
+        }
+    }
+    magic
+}
+
+struct Sliders {
+    rook: Vec<Magic>,
+    bishop: Vec<Magic>,
+    table: Vec<Bitboard>,
+}
+
+impl Sliders {
+    fn new() -> Self {
+        let mut table = Vec::new();
+        let rook = (0..64)
+            .map(|from| fill(from, ROOK_MAGICS[from], &ROOK_DIRECTIONS, &mut table))
+            .collect();
+        let bishop = (0..64)
+            .map(|from| fill(from, BISHOP_MAGICS[from], &BISHOP_DIRECTIONS, &mut table))
+            .collect();
+        Sliders {
+            rook,
+            bishop,
+            table,
+        }
+    }
+}
+
+lazy_static! {
+    static ref SLIDERS: Sliders = Sliders::new();
+}
+
+pub fn rook_attacks(from: usize, occupied: Bitboard) -> Bitboard {
+    SLIDERS.table[SLIDERS.rook[from].slot(occupied)]
+}
+
+pub fn bishop_attacks(from: usize, occupied: Bitboard) -> Bitboard {
+    SLIDERS.table[SLIDERS.bishop[from].slot(occupied)]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Bitboards {
+    pub pieces: [[Bitboard; 6]; 2], // [white, black][pawn, knight, bishop, rook, queen, king]
+    pub colors: [Bitboard; 2],      // Every piece of white and black
+    pub occupied: Bitboard,
+}
+
+impl Bitboards {
+    pub fn from_grid(grid: &Grid) -> Self {
+        let mut bitboards = Bitboards::default();
+        for (index, &piece) in grid.iter().flatten().enumerate() {
+            bitboards.put(index, piece);
+        }
+        bitboards
+    }
+
+    /// Adds the piece to the square, ' ' adds nothing.
+    pub fn put(&mut self, index: usize, piece: char) {
+        if let Some(kind) = kind(piece) {
+            let color = side(is_white(piece));
+            self.pieces[color][kind] |= bit(index);
+            self.colors[color] |= bit(index);
+            self.occupied |= bit(index);
+        }
+    }
+
+    /// Takes the piece off the square, ' ' takes nothing.
+    pub fn remove(&mut self, index: usize, piece: char) {
+        if let Some(kind) = kind(piece) {
+            let color = side(is_white(piece));
+            self.pieces[color][kind] &= !bit(index);
+            self.colors[color] &= !bit(index);
+            self.occupied &= !bit(index);
+        }
+    }
+
+    pub fn king(&self, white: bool) -> Option<usize> {
+        match self.pieces[side(white)][KING] {
+            0 => None, // Some test positions don't have kings at all
+            king => Some(king.trailing_zeros() as usize),
+        }
+    }
+
+    /// Squares the piece attacks from the square, whatever stands on them.
+    pub fn attacks(&self, piece: char, from: usize) -> Bitboard {
+        match kind(piece) {
+            Some(PAWN) => PAWN_ATTACKS[side(is_white(piece))][from],
+            Some(KNIGHT) => KNIGHT_ATTACKS[from],
+            Some(BISHOP) => bishop_attacks(from, self.occupied),
+            Some(ROOK) => rook_attacks(from, self.occupied),
+            Some(QUEEN) => bishop_attacks(from, self.occupied) | rook_attacks(from, self.occupied),
+            Some(KING) => KING_ATTACKS[from],
+            _ => 0,
+        }
+    }
+
+    /// Pieces of the `by_white` side which attack the square.
+    pub fn attackers(&self, target: usize, by_white: bool) -> Bitboard {
+        let pieces = &self.pieces[side(by_white)];
+        let straight = pieces[ROOK] | pieces[QUEEN];
+        let diagonal = pieces[BISHOP] | pieces[QUEEN];
+        // A pawn attacks the target if a pawn of the other color on the target would attack it
+        (PAWN_ATTACKS[side(!by_white)][target] & pieces[PAWN])
+            | (KNIGHT_ATTACKS[target] & pieces[KNIGHT])
+            | (KING_ATTACKS[target] & pieces[KING])
+            | (rook_attacks(target, self.occupied) & straight)
+            | (bishop_attacks(target, self.occupied) & diagonal)
+    }
+
+    pub fn is_attacked(&self, target: usize, by_white: bool) -> bool {
+        self.attackers(target, by_white) != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_squares() {
+        assert_eq!(index([1, 1]), 0); // a8
+        assert_eq!(index([8, 8]), 63); // h1
+        assert_eq!(square(index([5, 7])), [5, 7]);
+        let set = bit(index([2, 3])) | bit(index([7, 1]));
+        assert_eq!(squares(set).collect::<Vec<_>>(), vec![6, 17]);
+    }
+
+    #[test]
+    fn test_step_attacks() {
+        assert_eq!(KNIGHT_ATTACKS[index([1, 8])].count_ones(), 2); // a1
+        assert_eq!(KNIGHT_ATTACKS[index([5, 4])].count_ones(), 8); // e5
+        assert_eq!(KING_ATTACKS[index([8, 1])].count_ones(), 3); // h8
+        let e4 = index([5, 5]);
+        assert_eq!(
+            PAWN_ATTACKS[0][e4],
+            bit(index([4, 4])) | bit(index([6, 4])) // d5, f5
+        );
+    }
+
+    // Xorshift, random blockers for checking the magic numbers
+    struct Random(u64);
+
+    impl Random {
+        fn sparse(&mut self) -> u64 {
+            let mut next = || {
+                self.0 ^= self.0 >> 12;
+                self.0 ^= self.0 << 25;
+                self.0 ^= self.0 >> 27;
+                self.0.wrapping_mul(2685821657736338717)
+            };
+            next() & next()
+        }
+    }
+
+    #[test]
+    fn test_magics_match_sliding() {
+        let mut random = Random(7);
+        for from in 0..64 {
+            for _ in 0..200 {
+                let occupied = random.sparse() | random.sparse();
+                assert_eq!(
+                    rook_attacks(from, occupied),
+                    slide(from, occupied, &ROOK_DIRECTIONS)
+                );
+                assert_eq!(
+                    bishop_attacks(from, occupied),
+                    slide(from, occupied, &BISHOP_DIRECTIONS)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_attackers() {
+        let mut grid = [[' '; 8]; 8];
+        grid[3][3] = 'R'; // d5
+        grid[5][2] = 'p'; // c3
+        let bitboards = Bitboards::from_grid(&grid);
+        let d1 = index([4, 8]);
+        assert_eq!(bitboards.attackers(d1, true), bit(index([4, 4])));
+        assert!(bitboards.is_attacked(index([2, 7]), false)); // b2
+        assert!(!bitboards.is_attacked(index([3, 2]), false)); // c7, pawns only take forward
+    }
+}
@@ -0,0 +1,139 @@
+/*
+ * File: castling.rs
+ * Purpose: Contains the CastlingRights struct (KQkq part of the FEN)
+ * Date: 18.10.2026
+ * */
+
+/* In Chess960 the king and the rooks can start on any file of the back
+ * rank, so the rights also remember where the castling rooks started.
+ * Wherever they start, castling puts them on the normal squares:
+ *
+ *  short castle   king -> g file   rook -> f file
+ *  long castle    king -> c file   rook -> d file
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CastlingRights {
+    pub white_king: bool,            // K -> white can castle short
+    pub white_queen: bool,           // Q -> white can castle long
+    pub black_king: bool,            // k -> black can castle short
+    pub black_queen: bool,           // q -> black can castle long
+    pub rook_files: [[usize; 2]; 2], // [white, black][short, long] starting files of the rooks
+}
+
+/// Row of the back rank, counted from the top like Move does.
+pub fn back_row(white: bool) -> usize {
+    if white {
+        8
+    } else {
+        1
+    }
+}
+
+impl CastlingRights {
+    pub fn none() -> Self {
+        CastlingRights {
+            white_king: false,
+            white_queen: false,
+            black_king: false,
+            black_queen: false,
+            rook_files: [[8, 1], [8, 1]],
+        }
+    }
+
+    /// Starting file of the rook which castles to that side.
+    pub fn rook_file(&self, white: bool, king_side: bool) -> usize {
+        self.rook_files[!white as usize][!king_side as usize]
+    }
+
+    /// Where the rook starts, where the king lands and where the rook lands.
+    pub fn squares(&self, white: bool, king_side: bool) -> ([usize; 2], [usize; 2], [usize; 2]) {
+        let row = back_row(white);
+        let (king_to, rook_to) = if king_side { (7, 6) } else { (3, 4) };
+        (
+            [self.rook_file(white, king_side), row],
+            [king_to, row],
+            [rook_to, row],
+        )
+    }
+
+    fn right(&mut self, white: bool, king_side: bool) -> &mut bool {
+        match (white, king_side) {
+            (true, true) => &mut self.white_king,
+            (true, false) => &mut self.white_queen,
+            (false, true) => &mut self.black_king,
+            (false, false) => &mut self.black_queen,
+        }
+    }
+
+    /// Gives the right to castle with the rook starting on the file.
+    pub fn grant(&mut self, white: bool, king_side: bool, rook_file: usize) {
+        *self.right(white, king_side) = true;
+        self.rook_files[!white as usize][!king_side as usize] = rook_file;
+    }
+
+    /// Returns true if the side still has the right to castle that way.
+    pub fn allows(&self, white: bool, king_side: bool) -> bool {
+        match (white, king_side) {
+            (true, true) => self.white_king,
+            (true, false) => self.white_queen,
+            (false, true) => self.black_king,
+            (false, false) => self.black_queen,
+        }
+    }
+
+    /// Called when a king moves, it can't castle to either side anymore.
+    pub fn remove_side(&mut self, white: bool) {
+        match white {
+            true => (self.white_king, self.white_queen) = (false, false),
+            false => (self.black_king, self.black_queen) = (false, false),
+        }
+    }
+
+    /// Called when something moves from or to a square. If it is the
+    /// starting square of a rook, that rook can't castle anymore
+    /// (it either moved or got captured).
+    pub fn touch(&mut self, square: [usize; 2]) {
+        for white in [true, false] {
+            for king_side in [true, false] {
+                if square == [self.rook_file(white, king_side), back_row(white)] {
+                    *self.right(white, king_side) = false;
+                }
+            }
+        }
+    }
+}
+
+impl Default for CastlingRights {
+    fn default() -> Self {
+        CastlingRights {
+            white_king: true,
+            white_queen: true,
+            black_king: true,
+            black_queen: true,
+            rook_files: [[8, 1], [8, 1]],
+        }
+    }
+}
+
+impl Display for CastlingRights {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let rights = [
+            (self.white_king, 'K'),
+            (self.white_queen, 'Q'),
+            (self.black_king, 'k'),
+            (self.black_queen, 'q'),
+        ];
+        let field: String = rights
+            .iter()
+            .filter(|(allowed, _)| *allowed)
+            .map(|(_, ch)| ch)
+            .collect();
+        match field.is_empty() {
+            true => write!(f, "-"),
+            false => write!(f, "{}", field),
+        }
+    }
+}
@@ -0,0 +1,185 @@
+/*
+ * File: chess960.rs
+ * Purpose: Making the start positions of Chess960 (Fischer Random Chess)
+ * Date: 18.10.2026
+ * */
+
+/* The pieces of the back rank are shuffled with two rules: the bishops
+ * stand on different colors and the king stands between the rooks.
+ * Black mirrors white. There are 960 such positions, numbered like this
+ * (Scharnagl numbering, 518 is the normal start position):
+ *
+ *  n % 4          light-square bishop on b, d, f or h
+ *  n / 4 % 4      dark-square bishop on a, c, e or g
+ *  n / 16 % 6     queen on one of the 6 empty squares left
+ *  n / 96         knights on two of the 5 empty squares left (10 ways)
+ *
+ * Rook, king and rook go to the last 3 empty squares in this order.
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use super::castling::CastlingRights;
+use super::{Board, DEFAULT_PIECE_NOTATION};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const CHESS960_POSITIONS: usize = 960;
+
+// Empty squares the two knights take, for n / 96
+const KNIGHTS: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+/// White back rank of the position from a to h, like "RNBQKBNR" for 518.
+pub fn back_rank(index: usize) -> Option<[char; 8]> {
+    if index >= CHESS960_POSITIONS {
+        return None;
+    }
+    let mut rank = [' '; 8];
+    let mut n = index;
+    rank[n % 4 * 2 + 1] = 'B';
+    n /= 4;
+    rank[n % 4 * 2] = 'B';
+    n /= 4;
+
+    // Puts the piece on the i-th empty square
+    let place = |rank: &mut [char; 8], i: usize, piece: char| {
+        let file = (0..8).filter(|&file| rank[file] == ' ').nth(i).unwrap();
+        rank[file] = piece;
+    };
+    place(&mut rank, n % 6, 'Q');
+    n /= 6;
+    let (first, second) = KNIGHTS[n];
+    place(&mut rank, second, 'N'); // The second one first, so the first keeps its place
+    place(&mut rank, first, 'N');
+    for piece in ['R', 'K', 'R'] {
+        place(&mut rank, 0, piece);
+    }
+    Some(rank)
+}
+
+impl Board {
+    /// Chess960 start position by its number (0 to 959), None if there is no such position.
+    pub fn chess960(index: usize) -> Option<Board> {
+        let rank = back_rank(index)?;
+        let white: String = rank.iter().collect();
+        let black = white.to_ascii_lowercase();
+        // Same as the normal start position with the back ranks changed
+        let pawns = &DEFAULT_PIECE_NOTATION[8..56];
+
+        let mut board = Board {
+            FEN: format!("{}{}{}", black, pawns, white),
+            chess960: true,
+            ..Board::default()
+        };
+        board.decode();
+        let rooks: Vec<usize> = (1..=8).filter(|&file| rank[file - 1] == 'R').collect();
+        let mut castling = CastlingRights::none();
+        for white in [true, false] {
+            castling.grant(white, false, rooks[0]);
+            castling.grant(white, true, rooks[1]);
+        }
+        board.castling = castling;
+        board.hash = board.compute_hash();
+        Some(board)
+    }
+
+    /// One of the 960 start positions picked at random.
+    pub fn random_chess960() -> Board {
+        // Nanoseconds of the clock are random enough for picking a position
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.subsec_nanos() as usize);
+        Board::chess960(nanos / 1000 % CHESS960_POSITIONS).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Move;
+    use std::collections::HashSet;
+
+    fn rank(index: usize) -> String {
+        back_rank(index).unwrap().iter().collect()
+    }
+
+    #[test]
+    fn test_numbering() {
+        assert_eq!(rank(518), "RNBQKBNR");
+        assert_eq!(rank(0), "BBQNNRKR");
+        assert_eq!(rank(959), "RKRNNQBB");
+        assert!(back_rank(960).is_none());
+
+        // Every number makes a different position which follows the rules
+        let ranks: HashSet<String> = (0..CHESS960_POSITIONS).map(rank).collect();
+        assert_eq!(ranks.len(), CHESS960_POSITIONS);
+        for rank in ranks {
+            let files = |piece| {
+                rank.match_indices(piece)
+                    .map(|(i, _)| i)
+                    .collect::<Vec<_>>()
+            };
+            let (bishops, rooks, king) = (files('B'), files('R'), files('K'));
+            assert_ne!(bishops[0] % 2, bishops[1] % 2, "{}", rank);
+            assert!(rooks[0] < king[0] && king[0] < rooks[1], "{}", rank);
+        }
+    }
+
+    #[test]
+    fn test_start_position() {
+        let board = Board::chess960(518).unwrap();
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+        );
+        assert_eq!(board.hash, Board::default().hash);
+
+        let board = Board::chess960(0).unwrap();
+        assert_eq!(
+            board.to_fen(),
+            "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1"
+        );
+        assert_eq!(board.legal_moves().len(), 20);
+        assert!(Board::random_chess960().chess960);
+    }
+
+    #[test]
+    fn test_castling() {
+        // King on b1 and rook on a1: the king goes to c1 and the rook jumps over it to d1
+        let fen = "rk2r3/pppppppp/8/8/8/8/PPPPPPPP/RK2R3 w KQkq - 0 1";
+        let mut board = Board::from_fen(fen).unwrap();
+        assert!(board.chess960);
+        let long = Move::new([2, 8], [1, 8]); // King takes its rook
+        assert!(board.legal_moves().contains(&long));
+        assert_eq!(board.to_san(&long), "O-O-O");
+        board.move_piece(long).unwrap();
+        assert_eq!(
+            board.to_fen(),
+            "rk2r3/pppppppp/8/8/8/8/PPPPPPPP/2KRR3 b kq - 1 1"
+        );
+        board.undo_move();
+        assert_eq!(board.to_fen(), fen);
+
+        // King on g1 stays there and the rook on h1 goes to f1
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R5KR w HA - 0 1").unwrap();
+        board.move_piece(Move::new([7, 8], [8, 8])).unwrap();
+        assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/R4RK1 b - - 1 1");
+    }
+
+    #[test]
+    fn test_castling_rook_does_not_shield_the_king() {
+        // Rook on b1 blocks the rook on a1, but the king walks to c1 which a1 sees after castling
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/rR3K2 w B - 0 1").unwrap();
+        let long = Move::new([6, 8], [2, 8]);
+        assert!(!board.legal_moves().contains(&long));
+        assert!(board.move_piece(long).is_err());
+    }
+}
@@ -5,15 +5,8 @@
  * Date: 11.02.2023
  * */
 
-
-
-// TODO: Undoing undone moves lead to a problem
-// TODO: Undoing piece takes does not bring piece back
-// TODO: Check and Checkmate
-// TODO: Stalemate
-// TODO: Moves doesn't affect the board if not drawed (BUG)
-
 //use crate::board::char_to_piece;
+use super::{Board, Renderer};
 use std::error::Error;
 use std::fmt::{Display, Formatter};
 
@@ -26,14 +19,14 @@ pub enum MoveType {
     DoublePawn,
     PawnCapture,
     Capture,
-    Castle, // Kinda done, but so many bugs, row validation, moved piece validation etc.
+    Castle,
     Promotion,
 
     EnPassant, // TODO
 }
 
-#[derive(Clone)]
-pub struct Move([usize; 2], [usize; 2]); // start(x, y), end(x, y)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Move([usize; 2], [usize; 2], Option<char>); // start(x, y), end(x, y), promotion
 
 #[derive(Debug, Clone)]
 pub struct MoveErr(pub String);
@@ -121,8 +114,6 @@ const BISHOP_MOVE: [i8; 4] = [7, 9, -7, -9];
 const ROOK_MOVE: [i8; 4] = [1, 8, -1, -8];
 const QUEEN_MOVE: [i8; 8] = [1, 7, 8, 9, -1, -7, -8, -9];
 
-static mut TAKEN_PIECE: Option<char> = None;
-
 impl Display for MoveErr {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
@@ -162,14 +153,25 @@ impl Offset {
 
 impl Move {
     pub fn new(start: [usize; 2], end: [usize; 2]) -> Self {
-        Move(start, end)
+        Move(start, end, None)
+    }
+
+    /// Same move with the piece a pawn turns into on the last row (q, r, b, n).
+    pub fn with_promotion(self, piece: char) -> Self {
+        Move(self.0, self.1, Some(piece.to_ascii_lowercase()))
+    }
+
+    /// Lowercase promotion piece, None if the move doesn't choose one.
+    pub fn promotion(&self) -> Option<char> {
+        self.2
     }
 
     pub fn decode_move(&self) -> ([usize; 2], [usize; 2]) {
         (self.0, self.1)
     }
 
-    pub fn validate_move(&self, fen: &String, turn: bool) -> Result<MoveType, MoveErr> {
+    #[allow(dead_code)]
+    pub fn validate_move(&self, fen: &str, turn: bool) -> Result<MoveType, MoveErr> {
         // self.0 is the start position
         // self.1 is the end position
         /*
@@ -191,17 +193,6 @@ impl Move {
 
         let from_move_index = fen_idx(self.0) as i8;
         let to_move_index = fen_idx(self.1);
-        let taken_piece = fen
-            .chars()
-            .nth(to_move_index as usize - 1)
-            .expect("OUT OF BOUNDS");
-
-        unsafe {
-            TAKEN_PIECE = match taken_piece {
-                ' ' => None,
-                _ => Some(taken_piece),
-            };
-        }
         let moved_piece = fen
             .chars()
             .nth(from_move_index as usize - 1)
@@ -217,76 +208,76 @@ impl Move {
                 if non_contigious_move_check(PAWN_MOVE.to_vec(), diff)
                     && is_empty(fen, to_move_index)
                 {
-                    if to_move_index > 56 || to_move_index < 9 {
+                    if !(9..=56).contains(&to_move_index) {
                         return Ok(MoveType::Promotion);
                     }
-                    return Ok(MoveType::Regular);
+                    Ok(MoveType::Regular)
                 }
                 // Has bugs!
                 else if non_contigious_move_check(NM_PAWN_MOVE.to_vec(), diff)
                     && is_empty_till_n(fen, to_move_index, Offset::Up, 2, turn)
                     && pawn_first_move(fen, from_move_index, turn)
                 {
-                    return Ok(MoveType::DoublePawn);
+                    Ok(MoveType::DoublePawn)
                 } else if PAWN_CAPTURE.contains(&diff) {
                     if is_full(fen, to_move_index) {
                         return Ok(MoveType::PawnCapture);
                     }
 
                     if en_passant_check(fen, to_move_index, factor, diff) {
-                        return Ok(MoveType::EnPassant);
+                        Ok(MoveType::EnPassant)
                     } else {
-                        return Err(MoveErr("Invalid Pawn capture".to_owned()));
+                        Err(MoveErr("Invalid Pawn capture".to_owned()))
                     }
                 } else {
-                    return Err(MoveErr("Invalid Pawn move".to_owned()));
+                    Err(MoveErr("Invalid Pawn move".to_owned()))
                 }
             }
             'R' => {
                 if contigious_move_check(ROOK_MOVE.to_vec(), from_move_index, diff, fen, factor) {
                     /* I need some way to represent Contigious moves */
                     if is_full(fen, to_move_index) {
-                        return Ok(MoveType::Capture);
+                        Ok(MoveType::Capture)
                     } else {
-                        return Ok(MoveType::Regular);
+                        Ok(MoveType::Regular)
                     }
                 } else {
-                    return Err(MoveErr("Invalid Rook move".to_owned()));
+                    Err(MoveErr("Invalid Rook move".to_owned()))
                 }
             }
             'N' => {
                 // Knight can move in L shape and can jump over other pieces
                 if KNIGHT_MOVE.contains(&diff) {
                     if is_full(fen, to_move_index) {
-                        return Ok(MoveType::Capture);
+                        Ok(MoveType::Capture)
                     } else {
-                        return Ok(MoveType::Regular);
+                        Ok(MoveType::Regular)
                     }
                 } else {
-                    return Err(MoveErr("Invalid Knight move".to_owned()));
+                    Err(MoveErr("Invalid Knight move".to_owned()))
                 }
             }
             'B' => {
                 // Bishop can move diagonally. TODO Does not check if there is a piece in between
                 if contigious_move_check(BISHOP_MOVE.to_vec(), from_move_index, diff, fen, factor) {
                     if is_full(fen, to_move_index) {
-                        return Ok(MoveType::Capture);
+                        Ok(MoveType::Capture)
                     } else {
-                        return Ok(MoveType::Regular);
+                        Ok(MoveType::Regular)
                     }
                 } else {
-                    return Err(MoveErr("Invalid Bishop move".to_owned()));
+                    Err(MoveErr("Invalid Bishop move".to_owned()))
                 }
             }
             'Q' => {
                 if contigious_move_check(QUEEN_MOVE.to_vec(), from_move_index, diff, fen, factor) {
                     if is_full(fen, to_move_index) {
-                        return Ok(MoveType::Capture);
+                        Ok(MoveType::Capture)
                     } else {
-                        return Ok(MoveType::Regular);
+                        Ok(MoveType::Regular)
                     }
                 } else {
-                    return Err(MoveErr("Invalid Queen move".to_owned()));
+                    Err(MoveErr("Invalid Queen move".to_owned()))
                 }
             }
             'K' => {
@@ -302,9 +293,9 @@ impl Move {
                     // TODO Move these is_empty to a move method
 
                     if (is_empty(fen, to_move_index)
-                        && is_empty(fen, to_move_index + 1 * factor as i32)) // short castling:
+                        && is_empty(fen, to_move_index + (factor as i32))) // short castling:
                         || (is_empty(fen, to_move_index)
-                            && is_empty(fen, to_move_index + 1 * -factor as i32))
+                            && is_empty(fen, to_move_index + (-factor as i32)))
                     // long castling:
                     {
                         // TODO Add here
@@ -325,27 +316,28 @@ impl Move {
                 // seems to work but not sure. TODO != (maybe?)
                 {
                     if is_full(fen, to_move_index) {
-                        return Ok(MoveType::Capture);
+                        Ok(MoveType::Capture)
                     } else {
-                        return Ok(MoveType::Regular);
+                        Ok(MoveType::Regular)
                     }
                 } else {
-                    return Err(MoveErr("Invalid King move".to_owned()));
+                    Err(MoveErr("Invalid King move".to_owned()))
                 }
             }
-            _ => {
-                return Err(MoveErr("No valid piece found".to_owned()));
-            }
+            _ => Err(MoveErr("No valid piece found".to_owned())),
         }
     }
 
+    /// Draws the board with this move highlighted before it is played, like a hint.
     #[allow(dead_code)]
-    pub fn draw_gui(&self) {
-        todo!()
+    pub fn draw_gui(&self, board: &Board, renderer: &mut dyn Renderer) {
+        let mut snapshot = board.snapshot();
+        snapshot.last_move = Some(self.decode_move());
+        renderer.render(&snapshot, &board.ui_state());
     }
 
     #[allow(dead_code)]
-    pub fn moved_piece(&self, fen: &String) -> char {
+    pub fn moved_piece(&self, fen: &str) -> char {
         // There is a -1 because the index starts from 0
         fen.chars()
             .nth(fen_idx(self.0) as usize - 1)
@@ -364,27 +356,26 @@ impl Move {
 
     #[allow(dead_code)]
     #[allow(unused_variables)]
-    pub fn rate_move_slight(&self) -> i32 {
+    pub fn rate_move_slight(&self, fen: &str) -> i32 {
         // simple evaluation
-        // evaulate how good was the move based on the board
-        unsafe {
-            match TAKEN_PIECE {
-                Some(x) => match x.to_ascii_uppercase() {
-                    'P' => 1,
-                    'N' => 3,
-                    'B' => 3,
-                    'R' => 5,
-                    'Q' => 9,
-                    _ => 0,
-                },
-                None => 0,
-            }
+        // evaulate how good was the move based on the board (before the move is made)
+        let taken_piece = fen
+            .chars()
+            .nth(fen_idx(self.1) as usize - 1)
+            .expect("OUT OF BOUNDS");
+        match taken_piece.to_ascii_uppercase() {
+            'P' => 1,
+            'N' => 3,
+            'B' => 3,
+            'R' => 5,
+            'Q' => 9,
+            _ => 0,
         }
     }
 
     #[allow(dead_code)]
     #[allow(unused_variables)]
-    pub fn rate_move(&self, fen: &String) -> i32 {
+    pub fn rate_move(&self, fen: &str) -> i32 {
         // TODO
         // evaulate how good was the move based on the board
         // Will evaluate the board after the move is made
@@ -406,36 +397,38 @@ impl Move {
 
 // This is bare required for the move to be valid (Will do more checks in board.rs)
 //                                                 ^^^^^--- Moved? and clear taken piece
-fn en_passant_check(fen: &String, to_move_index: i32, factor: i8, diff: i8) -> bool {
+fn en_passant_check(fen: &str, to_move_index: i32, factor: i8, _diff: i8) -> bool {
     // It could be 9, 7, -9, -7 (depending on the color and the direction)
     let near_piece = dbg!(fen
         .chars()
         .nth((to_move_index as i8 + 8 * -factor - 1) as usize)
         .unwrap());
-    if near_piece.to_ascii_lowercase() == 'p' {
+    if near_piece.eq_ignore_ascii_case(&'p') {
         is_opposite_color(near_piece, factor)
     } else {
-        return false;
+        false
     }
 }
 
-fn pawn_first_move(fen: &String, from_move_index: i8, turn: bool) -> bool {
+fn pawn_first_move(fen: &str, from_move_index: i8, turn: bool) -> bool {
     //
     match turn {
         true => {
-            if fen.chars().nth(from_move_index as usize - 1).unwrap() == 'P' {
-                if from_move_index > 48 && from_move_index <= 56 {
-                    // if it's in the same place as
-                    // it was before the game started
-                    return true;
-                }
+            if fen.chars().nth(from_move_index as usize - 1).unwrap() == 'P'
+                && from_move_index > 48
+                && from_move_index <= 56
+            {
+                // if it's in the same place as
+                // it was before the game started
+                return true;
             }
         }
         false => {
-            if fen.chars().nth(from_move_index as usize - 1).unwrap() == 'p' {
-                if from_move_index > 8 && from_move_index <= 16 {
-                    return true;
-                }
+            if fen.chars().nth(from_move_index as usize - 1).unwrap() == 'p'
+                && from_move_index > 8
+                && from_move_index <= 16
+            {
+                return true;
             }
         }
     }
@@ -449,11 +442,11 @@ fn is_opposite_color(piece: char, factor: i8) -> bool {
      * so we can only capture black pieces
      * or vice versa
      * */
-    return match factor {
+    match factor {
         -1 => is_black(piece),
         1 => is_white(piece),
         _ => panic!("Invalid factor"),
-    };
+    }
 }
 
 fn is_black(piece: char) -> bool {
@@ -465,7 +458,7 @@ fn contigious_move_check(
     legal_moves: Vec<i8>,
     start: i8,
     diff: i8,
-    fen: &String,
+    fen: &str,
     factor: i8,
 ) -> bool {
     let piece_is_white = {
@@ -523,7 +516,7 @@ pub fn is_white(piece: char) -> bool {
 }
 
 // TODO make this more efficient
-//fn contigious_move_check(legal_moves: Vec<i8>, start:i8, diff: i8, fen: &String, factor: i8) -> bool {
+//fn contigious_move_check(legal_moves: Vec<i8>, start:i8, diff: i8, fen: &str, factor: i8) -> bool {
 //let mut result = dbg!(diff);
 //let mut has_piece_between = false;
 //for x in dbg!(legal_moves) {
@@ -546,7 +539,7 @@ pub fn is_white(piece: char) -> bool {
 
 /* // Tried This:
 
-fn contigious_move_check(legal_moves: Vec<i8>, start:i8, diff: i8, fen: &String, factor: i8) -> bool {
+fn contigious_move_check(legal_moves: Vec<i8>, start:i8, diff: i8, fen: &str, factor: i8) -> bool {
     let mut result = dbg!(diff);
     let mut has_piece_between = false;
     for x in dbg!(legal_moves) {
@@ -570,7 +563,25 @@ fn contigious_move_check(legal_moves: Vec<i8>, start:i8, diff: i8, fen: &String,
 */
 
 fn non_contigious_move_check(legal_moves: Vec<i8>, diff: i8) -> bool {
-    legal_moves.iter().any(|&x| x == diff)
+    legal_moves.contains(&diff)
+}
+
+/// Converts [x, y] coordinates to a square name. eg. [5, 7] -> "e2"
+pub fn square_name(square: [usize; 2]) -> String {
+    format!("{}{}", (b'a' + square[0] as u8 - 1) as char, 9 - square[1])
+}
+
+/// Converts a square name to [x, y] coordinates. eg. "e2" -> [5, 7]
+pub fn parse_square(name: &str) -> Option<[usize; 2]> {
+    let mut chars = name.chars();
+    let (file, rank) = (chars.next()?, chars.next()?);
+    if chars.next().is_some() || !('a'..='h').contains(&file) || !('1'..='8').contains(&rank) {
+        return None;
+    }
+    Some([
+        (file as u8 - b'a') as usize + 1,
+        9 - rank.to_digit(10)? as usize,
+    ])
 }
 
 pub fn fen_idx(moved: [usize; 2]) -> i32 {
@@ -578,13 +589,7 @@ pub fn fen_idx(moved: [usize; 2]) -> i32 {
     (moved[0] + (moved[1] - 1) * 8) as i32
 }
 
-fn contigious_move_check(
-    legal_moves: Vec<i8>,
-    start: i8,
-    diff: i8,
-    fen: &String,
-    factor: i8,
-) -> bool {
+fn contigious_move_check(legal_moves: Vec<i8>, start: i8, diff: i8, fen: &str, factor: i8) -> bool {
     let mut result = dbg!(start);
     let to = dbg!(start + diff * factor);
     let mut has_piece_between = false;
@@ -601,7 +606,7 @@ fn contigious_move_check(
             //}
             let index = result - 1;
 
-            if index < 0 || index > 63 {
+            if !(0..=63).contains(&index) {
                 break;
             } else {
                 let piece = dbg!(fen
@@ -625,7 +630,7 @@ fn contigious_move_check(
 }
 
 // TODO
-fn is_empty_till_n(fen: &String, to_move_index: i32, way: Offset, n: i8, turn: bool) -> bool {
+fn is_empty_till_n(fen: &str, to_move_index: i32, way: Offset, n: i8, turn: bool) -> bool {
     // Piece could be white or black
     // And piece can eat opponent's piece
     /* This algorithm first looks at the target
@@ -656,11 +661,11 @@ fn is_empty_till_n(fen: &String, to_move_index: i32, way: Offset, n: i8, turn: b
     true
 }
 
-fn is_empty(fen: &String, to_move_index: i32) -> bool {
+fn is_empty(fen: &str, to_move_index: i32) -> bool {
     !is_full(fen, to_move_index)
 }
 
-fn is_full(fen: &String, to_move_index: i32) -> bool {
+fn is_full(fen: &str, to_move_index: i32) -> bool {
     fen.chars()
         .nth(to_move_index as usize - 1)
         .unwrap_or(' ')
@@ -670,13 +675,14 @@ fn is_full(fen: &String, to_move_index: i32) -> bool {
 
 impl From<((usize, usize), (usize, usize))> for Move {
     fn from(m: ((usize, usize), (usize, usize))) -> Self {
-        Move([m.0 .0, m.0 .1], [m.1 .0, m.1 .1])
+        Move::new([m.0 .0, m.0 .1], [m.1 .0, m.1 .1])
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::board::GameResult;
     use crate::Board;
 
     #[test]
@@ -707,6 +713,17 @@ mod tests {
         assert_eq!(fen_idx([1, 3]), 17);
     }
 
+    #[test]
+    fn test_square_names() {
+        assert_eq!(square_name([5, 7]), "e2");
+        assert_eq!(square_name([1, 1]), "a8");
+        assert_eq!(parse_square("h1"), Some([8, 8]));
+        assert_eq!(parse_square("e2"), Some([5, 7]));
+        assert_eq!(parse_square("i2"), None);
+        assert_eq!(parse_square("e9"), None);
+        assert_eq!(parse_square("e22"), None);
+    }
+
     #[test]
     #[allow(unused_must_use)]
     fn test_general_game() {
@@ -852,17 +869,13 @@ mod tests {
     #[test]
     fn test_bishop_capture() {
         // This has to return an error because the bishop tries to eat a piece which is same color as itself
-        let mut board: Board = Board::from_fen(
-            "                      b                           p             ".to_owned(),
-        );
+        let mut board: Board = Board::from_fen("k7/8/6b1/8/8/8/2p5/7K w - - 0 1").unwrap();
         board.draw_ascii();
         let m_err = Move::new([7, 3], [3, 7]);
         let move_type_err = board.move_piece(m_err.clone());
         assert!(move_type_err.is_err());
 
-        let mut board: Board = Board::from_fen(
-            "                      B                           p             ".to_owned(),
-        );
+        let mut board: Board = Board::from_fen("k7/8/6B1/8/8/8/2p5/7K w - - 0 1").unwrap();
         let m_ok = Move::new([7, 3], [3, 7]);
         let move_type_ok = board.move_piece(m_ok.clone());
         assert!(move_type_ok.is_ok());
@@ -874,18 +887,14 @@ mod tests {
     #[test]
     fn test_rook_capture() {
         /* Forgot to reverse_turn! always white starts. shoot! */
-        let mut board = Board::from_fen(
-            "r      p                                                        ".to_owned(),
-        );
+        let mut board = Board::from_fen("r6n/8/8/8/8/8/8/k5K1 w - - 0 1").unwrap();
         board.reverse_turn();
         let m_err = Move::new([1, 1], [8, 1]);
         let move_type_err = board.move_piece(m_err.clone());
         assert!(move_type_err.is_err());
         board.draw_ascii();
 
-        let mut board = Board::from_fen(
-            "r      P                                                        ".to_owned(),
-        );
+        let mut board = Board::from_fen("r6N/8/8/8/8/8/8/k5K1 w - - 0 1").unwrap();
         board.reverse_turn();
         let m_ok = Move::new([1, 1], [8, 1]);
         let move_type_ok = board.move_piece(m_ok.clone()).unwrap();
@@ -896,9 +905,7 @@ mod tests {
 
     #[test]
     fn test_rook_move() {
-        let mut board = Board::from_fen(
-            "r                                                               ".to_owned(),
-        );
+        let mut board = Board::from_fen("r7/8/8/8/8/8/8/k5K1 w - - 0 1").unwrap();
         board.reverse_turn();
         let m_ok = Move::new([1, 1], [8, 1]);
         let move_type_ok = board.move_piece(m_ok.clone()).unwrap();
@@ -910,9 +917,7 @@ mod tests {
     // TODO add more comprehensive tests. (diagnal, horizontal, vertical)
     #[test]
     fn test_queen_move() {
-        let mut board = Board::from_fen(
-            "q                                                               ".to_owned(),
-        );
+        let mut board = Board::from_fen("q7/8/8/8/8/8/8/k5K1 w - - 0 1").unwrap();
         board.reverse_turn();
         let m_ok = Move::new([1, 1], [8, 1]);
         let move_type_ok = board.move_piece(m_ok.clone()).unwrap();
@@ -923,9 +928,7 @@ mod tests {
 
     #[test]
     fn test_queen_capture() {
-        let mut board = Board::from_fen(
-            "q      P                                                        ".to_owned(),
-        );
+        let mut board = Board::from_fen("q6N/8/8/8/8/8/8/k5K1 w - - 0 1").unwrap();
         board.reverse_turn();
         let m_ok = Move::new([1, 1], [8, 1]);
         let move_type_ok = board.move_piece(m_ok.clone()).unwrap();
@@ -936,9 +939,7 @@ mod tests {
 
     #[test]
     fn test_king_move() {
-        let mut board = Board::from_fen(
-            "k                                                               ".to_owned(),
-        );
+        let mut board = Board::from_fen("k7/8/8/8/8/8/8/6K1 w - - 0 1").unwrap();
         board.reverse_turn();
         let m_ok = Move::new([1, 1], [2, 1]);
         let move_type_ok = board.move_piece(m_ok.clone()).unwrap();
@@ -949,9 +950,7 @@ mod tests {
 
     #[test]
     fn test_king_capture() {
-        let mut board = Board::from_fen(
-            "kP                                                               ".to_owned(),
-        );
+        let mut board = Board::from_fen("kN6/8/8/8/8/8/8/6K1 w - - 0 1").unwrap();
         board.reverse_turn();
         let m_ok = Move::new([1, 1], [2, 1]);
         let move_type_ok = board.move_piece(m_ok.clone()).unwrap();
@@ -961,40 +960,256 @@ mod tests {
     }
 
     #[test]
-    fn test_king_castle() {}
+    fn test_king_castle() {
+        let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let move_type = board.move_piece(Move::new([5, 8], [7, 8])).unwrap(); // O-O
+        assert_eq!(move_type, MoveType::Castle);
+        assert_eq!(board.board[7][5], 'R');
+        assert_eq!(board.board[7][6], 'K');
+        assert_eq!(board.board[7][7], ' ');
+        assert!(!board.castling.white_king && !board.castling.white_queen);
+
+        // Black rook leaves h8 and comes back, short castle is gone for good
+        board.move_piece(Move::new([8, 1], [8, 2])).unwrap();
+        board.move_piece(Move::new([6, 8], [6, 7])).unwrap();
+        board.move_piece(Move::new([8, 2], [8, 1])).unwrap();
+        board.move_piece(Move::new([6, 7], [6, 8])).unwrap();
+        let err = board.move_piece(Move::new([5, 1], [7, 1])).unwrap_err();
+        assert_eq!(err.0, "King or rook has already moved");
+        assert!(board.move_piece(Move::new([5, 1], [3, 1])).is_ok()); // O-O-O
+        assert_eq!(board.to_fen(), "2kr3r/8/8/8/8/8/8/R4RK1 w - - 6 4");
+    }
 
     #[test]
-    fn test_queen_castle() {}
+    fn test_queen_castle() {
+        // Knight on b1 is in the way, even though the king doesn't walk over it
+        let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/RN2K2R w KQkq - 0 1").unwrap();
+        let err = board.move_piece(Move::new([5, 8], [3, 8])).unwrap_err();
+        assert_eq!(err.0, "There are pieces between the king and the rook");
+
+        // Rook on d8 attacks d1 which the king has to pass through
+        let mut board = Board::from_fen("3rk3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        let err = board.move_piece(Move::new([5, 8], [3, 8])).unwrap_err();
+        assert_eq!(
+            err.0,
+            "King can't pass through or land on an attacked square"
+        );
+
+        // Taking the rook on a8 takes away black's long castle
+        let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let move_type = board.move_piece(Move::new([1, 8], [1, 1])).unwrap();
+        assert_eq!(move_type, MoveType::Capture);
+        assert_eq!(board.castling.to_string(), "Kk");
+        let err = board.move_piece(Move::new([5, 1], [3, 1])).unwrap_err();
+        assert_eq!(err.0, "King or rook has already moved");
+    }
 
     #[test]
-    fn test_en_passant() {}
+    fn test_en_passant() {
+        let mut board: Board = Default::default();
+        board.move_piece(Move::new([5, 7], [5, 5])).unwrap(); // e4
+        board.move_piece(Move::new([1, 2], [1, 3])).unwrap(); // a6
+        board.move_piece(Move::new([5, 5], [5, 4])).unwrap(); // e5
+        board.move_piece(Move::new([4, 2], [4, 4])).unwrap(); // d5
+        assert_eq!(board.en_passant, Some([4, 3]));
+
+        let m = Move::new([5, 4], [4, 3]); // exd6
+        assert!(board.legal_moves().contains(&m));
+        let move_type = board.move_piece(m).unwrap();
+        assert_eq!(move_type, MoveType::EnPassant);
+        assert_eq!(board.board[3][3], ' '); // d5 pawn is taken
+        assert_eq!(board.board[2][3], 'P');
+    }
 
     #[test]
-    fn test_promotion() {}
+    fn test_promotion() {
+        let fen = "r3k3/1P6/8/8/8/8/6p1/4K2R w K - 0 1";
+        let mut board = Board::from_fen(fen).unwrap();
+        let move_type = board
+            .move_piece(Move::new([2, 2], [1, 1]).with_promotion('n')) // bxa8=N
+            .unwrap();
+        assert_eq!(move_type, MoveType::Promotion);
+        assert_eq!(board.board[0][0], 'N');
+
+        // Without a piece the pawn becomes a queen, even when it takes
+        board.move_piece(Move::new([7, 7], [8, 8])).unwrap(); // gxh1
+        assert_eq!(board.board[7][7], 'q');
+        assert_eq!(board.history.last().unwrap().played.promotion(), Some('q'));
+
+        // Both promotions are undone in one step each
+        board.undo_move();
+        board.undo_move();
+        assert_eq!(board.to_fen(), fen);
+
+        // Only pawns on the last row promote, and never to a king or a pawn
+        let e4 = Move::new([5, 7], [5, 5]).with_promotion('q');
+        assert!(Board::default().move_piece(e4).is_err());
+        assert!(board
+            .move_piece(Move::new([2, 2], [2, 1]).with_promotion('k'))
+            .is_err());
+        assert!(board
+            .move_piece(Move::new([2, 2], [2, 1]).with_promotion('p'))
+            .is_err());
+    }
 
     #[test]
-    fn test_check() {}
+    fn test_check() {
+        let mut board: Board = Default::default();
+        board.move_piece(Move::new([5, 7], [5, 5])).unwrap(); // e4
+        board.move_piece(Move::new([6, 2], [6, 3])).unwrap(); // f6
+        assert!(!board.is_check());
+        board.move_piece(Move::new([4, 8], [8, 4])).unwrap(); // Qh5+
+        assert!(board.is_check());
+        assert!(!board.is_checkmate());
+
+        // Only g6 blocks the check, everything else leaves the king attacked
+        assert_eq!(board.legal_moves(), vec![Move::new([7, 2], [7, 3])]);
+        assert!(board.move_piece(Move::new([1, 2], [1, 3])).is_err());
+    }
 
     #[test]
-    fn test_checkmate() {}
+    fn test_checkmate() {
+        let mut board: Board = Default::default();
+        board.move_piece(Move::new([6, 7], [6, 6])).unwrap(); // f3
+        board.move_piece(Move::new([5, 2], [5, 4])).unwrap(); // e5
+        board.move_piece(Move::new([7, 7], [7, 5])).unwrap(); // g4
+        assert_eq!(board.result(), None);
+        board.move_piece(Move::new([4, 1], [8, 5])).unwrap(); // Qh4#
+        assert!(board.is_checkmate());
+        assert!(board.legal_moves().is_empty());
+        assert_eq!(board.result(), Some(GameResult::Checkmate(false)));
+    }
 
     #[test]
-    fn test_stalemate() {}
+    fn test_stalemate() {
+        // Black king on h8 has no squares left
+        let board = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert!(!board.is_check());
+        assert!(board.is_stalemate());
+        assert!(!board.is_checkmate());
+        assert_eq!(board.result(), Some(GameResult::Stalemate));
+    }
 
     #[test]
-    fn test_invalid_move() {}
+    fn test_invalid_move() {
+        let mut board: Board = Default::default();
+        assert!(board.move_piece(Move::new([5, 7], [5, 7])).is_err()); // Same square
+        assert!(board.move_piece(Move::new([5, 7], [5, 9])).is_err()); // Off the board
+        assert!(board.move_piece(Move::new([5, 2], [5, 4])).is_err()); // Not black's turn
+        let err = board.move_piece(Move::new([5, 7], [5, 4])).unwrap_err();
+        assert_eq!(err.0, "Invalid Pawn move"); // Pawn can't jump 3 squares
+
+        // Pinned knight can't move away from the king
+        let mut board = Board::from_fen("k3r3/8/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+        let err = board.move_piece(Move::new([5, 7], [4, 5])).unwrap_err();
+        assert_eq!(err.0, "You can't leave your king in check");
+    }
 
     #[test]
-    fn test_insufficient_material() {}
+    fn test_insufficient_material() {
+        let dead = [
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1",    // K vs K
+            "4k3/8/8/8/8/8/8/4KN2 w - - 0 1",   // K+N vs K
+            "4kb2/8/8/8/8/8/8/4K3 w - - 0 1",   // K vs K+B
+            "4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1", // Bishops on the same color
+            "2b1k3/8/8/8/8/8/8/3BK3 b - - 0 1", // Bishops on the same color
+        ];
+        for fen in dead {
+            let board = Board::from_fen(fen).unwrap();
+            assert!(board.is_insufficient_material(), "{}", fen);
+            assert_eq!(board.result(), Some(GameResult::InsufficientMaterial));
+        }
+        let alive = [
+            "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1", // A pawn can promote
+            "4k3/8/8/8/8/8/8/4KNN1 w - - 0 1", // Two knights can be mated into
+            "4kb2/8/8/8/8/8/8/3BK3 w - - 0 1", // Bishops on different colors
+            "4kn2/8/8/8/8/8/8/3BK3 w - - 0 1", // Bishop and knight
+        ];
+        for fen in alive {
+            let board = Board::from_fen(fen).unwrap();
+            assert!(!board.is_insufficient_material(), "{}", fen);
+            assert_eq!(board.result(), None);
+        }
+
+        // Taking the last pawn leaves two lone kings
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/4p3/4K3 w - - 0 1").unwrap();
+        board.move_piece(Move::new([5, 8], [5, 7])).unwrap(); // Kxe2
+        assert_eq!(board.result(), Some(GameResult::InsufficientMaterial));
+    }
 
     #[test]
-    fn test_fifty_move_rule() {}
+    fn test_fifty_move_rule() {
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/4P3/R3K3 w - - 98 80").unwrap();
+        board.move_piece(Move::new([1, 8], [1, 7])).unwrap(); // Ra2
+        assert_eq!(board.claimable_draw(), None);
+        board.move_piece(Move::new([5, 1], [4, 1])).unwrap(); // Kd8
+        assert_eq!(board.halfmove_clock, 100);
+        assert_eq!(board.claimable_draw(), Some(GameResult::FiftyMoves));
+        assert_eq!(board.result(), None); // The game goes on until someone claims it
+
+        // A pawn move starts counting again
+        board.move_piece(Move::new([5, 7], [5, 6])).unwrap(); // e3
+        assert_eq!(board.halfmove_clock, 0);
+        assert_eq!(board.claimable_draw(), None);
+    }
 
     #[test]
-    fn test_threefold_repetition() {}
+    fn test_threefold_repetition() {
+        let mut board = Board::default();
+        let knights = [
+            Move::new([7, 8], [6, 6]), // Nf3
+            Move::new([7, 1], [6, 3]), // Nf6
+            Move::new([6, 6], [7, 8]), // Ng1
+            Move::new([6, 3], [7, 1]), // Ng8
+        ];
+        for current_move in knights.iter().cycle().take(4) {
+            board.move_piece(current_move.clone()).unwrap();
+        }
+        assert_eq!(board.claimable_draw(), None); // Second time only
+        for current_move in knights.iter().cycle().take(4) {
+            board.move_piece(current_move.clone()).unwrap();
+        }
+        assert_eq!(
+            board.claimable_draw(),
+            Some(GameResult::ThreefoldRepetition)
+        );
+        assert_eq!(board.result(), None);
+
+        // A pawn move makes the earlier positions unreachable
+        board.move_piece(Move::new([5, 7], [5, 5])).unwrap(); // e4
+        assert_eq!(board.claimable_draw(), None);
+    }
 
     #[test]
-    fn test_draw() {}
+    fn test_draw() {
+        // Fivefold repetition ends the game without a claim
+        let mut board = Board::default();
+        let knights = [
+            Move::new([7, 8], [6, 6]), // Nf3
+            Move::new([7, 1], [6, 3]), // Nf6
+            Move::new([6, 6], [7, 8]), // Ng1
+            Move::new([6, 3], [7, 1]), // Ng8
+        ];
+        for current_move in knights.iter().cycle().take(12) {
+            board.move_piece(current_move.clone()).unwrap();
+        }
+        assert_eq!(board.result(), None);
+        for current_move in knights.iter().cycle().take(4) {
+            board.move_piece(current_move.clone()).unwrap();
+        }
+        assert_eq!(board.result(), Some(GameResult::FivefoldRepetition));
+
+        // So do 75 moves without a capture or pawn move
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/4P3/R3K3 w - - 149 120").unwrap();
+        assert_eq!(board.result(), None);
+        board.move_piece(Move::new([1, 8], [1, 7])).unwrap(); // Ra2
+        assert_eq!(board.result(), Some(GameResult::SeventyFiveMoves));
+
+        // Unless the last move was a mate
+        let mut board = Board::from_fen("4k3/8/4K3/8/8/8/8/R7 w - - 149 120").unwrap();
+        board.move_piece(Move::new([1, 8], [1, 1])).unwrap(); // Ra8#
+        assert_eq!(board.result(), Some(GameResult::Checkmate(true)));
+    }
 
     #[test]
     fn test_draw_by_agreement() {}
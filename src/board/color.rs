@@ -17,8 +17,29 @@ pub mod themes {
     pub const GRUVBOX_DARK: BTheme = ((131, 148, 150), (40, 40, 40));
     pub const RUST: BTheme = ((219, 52, 0), (210, 191, 181));
     pub const BLANK: BTheme = ((0, 0, 0), (0, 0, 0));
+
+    // Names used by --theme
+    pub const NAMED: [(&str, BTheme); 5] = [
+        ("cotton-candy", COTTON_CANDY),
+        ("gruvbox", GRUVBOX),
+        ("gruvbox-dark", GRUVBOX_DARK),
+        ("rust", RUST),
+        ("blank", BLANK),
+    ];
+
+    pub fn find(name: &str) -> Option<BTheme> {
+        let name = name.to_lowercase().replace('_', "-");
+        NAMED
+            .iter()
+            .find(|(named, _)| *named == name)
+            .map(|(_, theme)| *theme)
+    }
 }
 
+// Mixed into the squares of the board for highlighting
+const LAST_MOVE: Color = Color(255, 213, 0);
+const CHECK: Color = Color(230, 30, 30);
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Color(u8, u8, u8);
 
@@ -43,6 +64,17 @@ impl Color {
         format!("\x1b[48;2;{};{};{}m", self.0, self.1, self.2)
     }
 
+    /// This color with `percent` of the other one in it.
+    pub fn mix(&self, other: Color, percent: u16) -> Color {
+        let channel =
+            |a: u8, b: u8| ((a as u16 * (100 - percent) + b as u16 * percent) / 100) as u8;
+        Color(
+            channel(self.0, other.0),
+            channel(self.1, other.1),
+            channel(self.2, other.2),
+        )
+    }
+
 }
 
 impl From<(u8, u8, u8)> for Color {
@@ -63,6 +95,16 @@ impl BoardColor {
         (&self.0, &self.1)
     }
 
+    /// Squares of the last move, the colors of the board stay a bit visible.
+    pub fn last_move(&self) -> (Color, Color) {
+        (self.0.mix(LAST_MOVE, 50), self.1.mix(LAST_MOVE, 50))
+    }
+
+    /// Square of a king in check.
+    pub fn check(&self) -> (Color, Color) {
+        (self.0.mix(CHECK, 70), self.1.mix(CHECK, 70))
+    }
+
 }
 
 impl From<BTheme> for BoardColor {
@@ -72,3 +114,21 @@ impl From<BTheme> for BoardColor {
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlights() {
+        assert_eq!(
+            Color(0, 100, 200).mix(Color(200, 100, 0), 50),
+            Color(100, 100, 100)
+        );
+        let board: BoardColor = themes::RUST.into();
+        let (light, dark) = board.last_move();
+        assert_ne!(light, dark);
+        assert_ne!(light, board.0);
+        assert_ne!(board.check().0, light);
+    }
+}
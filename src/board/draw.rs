@@ -0,0 +1,66 @@
+/*
+ * File: draw.rs
+ * Purpose: Finding the draws which don't come from stalemate
+ * Date: 18.10.2026
+ * */
+
+/* Some draws end the game right away, others only give a player the right
+ * to end it (FIDE Laws of Chess, article 9):
+ *
+ *  Automatic                          Claimable
+ *  fivefold repetition                threefold repetition
+ *  75 moves without capture or pawn   50 moves without capture or pawn
+ *  dead position                      -
+ *
+ * A move is a move of both sides, so the halfmove clock has to reach 100
+ * for 50 moves. Dead positions are only found when there isn't enough
+ * material for any mate: a lone king against a king with at most a knight
+ * or a bishop, or kings with bishops which all stand on the same color.
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use super::bitboard::{BISHOP, KNIGHT, LIGHT_SQUARES, PAWN, QUEEN, ROOK};
+use super::{Board, GameResult};
+
+impl Board {
+    /// Returns true if neither side has enough material to checkmate.
+    pub fn is_insufficient_material(&self) -> bool {
+        let [white, black] = &self.bitboards.pieces;
+        let mating = [PAWN, ROOK, QUEEN]
+            .iter()
+            .any(|&kind| white[kind] | black[kind] != 0);
+        if mating {
+            return false;
+        }
+        let knights = white[KNIGHT] | black[KNIGHT];
+        let bishops = white[BISHOP] | black[BISHOP];
+        if (knights | bishops).count_ones() <= 1 {
+            return true;
+        }
+        // Bishops on one color can never attack the squares of the other color
+        knights == 0 && (bishops & LIGHT_SQUARES == 0 || bishops & !LIGHT_SQUARES == 0)
+    }
+
+    /// Draw which ends the game even if nobody claims it.
+    pub fn automatic_draw(&self) -> Option<GameResult> {
+        if self.repetitions() >= 4 {
+            Some(GameResult::FivefoldRepetition)
+        } else if self.halfmove_clock >= 150 {
+            Some(GameResult::SeventyFiveMoves)
+        } else if self.is_insufficient_material() {
+            Some(GameResult::InsufficientMaterial)
+        } else {
+            None
+        }
+    }
+
+    /// Draw which the player to move can claim, the game goes on if they don't.
+    pub fn claimable_draw(&self) -> Option<GameResult> {
+        if self.repetitions() >= 2 {
+            Some(GameResult::ThreefoldRepetition)
+        } else if self.halfmove_clock >= 100 {
+            Some(GameResult::FiftyMoves)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,468 @@
+/*
+ * File: evaluation.rs
+ * Purpose: Scoring a position in centipawns without looking ahead
+ * Date: 18.10.2026
+ * */
+
+/* Every term is scored twice, once for the middlegame and once for the
+ * endgame. The two scores are blended by the phase of the game which is
+ * counted from the pieces left on the board (knight and bishop 1, rook 2,
+ * queen 4, so 24 is the starting position and 0 is a pawn ending):
+ *
+ *  score = (middlegame * phase + endgame * (24 - phase)) / 24
+ *
+ * Tables are written as white sees the board, 8th rank at the top, so a
+ * white piece on [x, y] uses TABLE[y - 1][x - 1] and black mirrors the row.
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use super::bitboard::{self, bit, side, squares, Bitboards, KING_ATTACKS};
+use super::chess_move::is_white;
+use super::movegen::{at, Grid};
+use super::Board;
+use std::fmt::{Display, Formatter};
+
+const MAX_PHASE: i32 = 24;
+
+type Table = [[i32; 8]; 8];
+
+#[rustfmt::skip]
+const PAWN_MG: Table = [
+    [  0,   0,   0,   0,   0,   0,   0,   0],
+    [ 50,  50,  50,  50,  50,  50,  50,  50],
+    [ 10,  10,  20,  30,  30,  20,  10,  10],
+    [  5,   5,  10,  25,  25,  10,   5,   5],
+    [  0,   0,   0,  20,  20,   0,   0,   0],
+    [  5,  -5, -10,   0,   0, -10,  -5,   5],
+    [  5,  10,  10, -20, -20,  10,  10,   5],
+    [  0,   0,   0,   0,   0,   0,   0,   0],
+];
+#[rustfmt::skip]
+const PAWN_EG: Table = [
+    [  0,   0,   0,   0,   0,   0,   0,   0],
+    [ 60,  60,  60,  60,  60,  60,  60,  60],
+    [ 40,  40,  40,  40,  40,  40,  40,  40],
+    [ 25,  25,  25,  25,  25,  25,  25,  25],
+    [ 15,  15,  15,  15,  15,  15,  15,  15],
+    [  5,   5,   5,   5,   5,   5,   5,   5],
+    [  0,   0,   0,   0,   0,   0,   0,   0],
+    [  0,   0,   0,   0,   0,   0,   0,   0],
+];
+#[rustfmt::skip]
+const KNIGHT: Table = [
+    [-50, -40, -30, -30, -30, -30, -40, -50],
+    [-40, -20,   0,   0,   0,   0, -20, -40],
+    [-30,   0,  10,  15,  15,  10,   0, -30],
+    [-30,   5,  15,  20,  20,  15,   5, -30],
+    [-30,   0,  15,  20,  20,  15,   0, -30],
+    [-30,   5,  10,  15,  15,  10,   5, -30],
+    [-40, -20,   0,   5,   5,   0, -20, -40],
+    [-50, -40, -30, -30, -30, -30, -40, -50],
+];
+#[rustfmt::skip]
+const BISHOP: Table = [
+    [-20, -10, -10, -10, -10, -10, -10, -20],
+    [-10,   0,   0,   0,   0,   0,   0, -10],
+    [-10,   0,   5,  10,  10,   5,   0, -10],
+    [-10,   5,   5,  10,  10,   5,   5, -10],
+    [-10,   0,  10,  10,  10,  10,   0, -10],
+    [-10,  10,  10,  10,  10,  10,  10, -10],
+    [-10,   5,   0,   0,   0,   0,   5, -10],
+    [-20, -10, -10, -10, -10, -10, -10, -20],
+];
+#[rustfmt::skip]
+const ROOK: Table = [
+    [  0,   0,   0,   0,   0,   0,   0,   0],
+    [  5,  10,  10,  10,  10,  10,  10,   5],
+    [ -5,   0,   0,   0,   0,   0,   0,  -5],
+    [ -5,   0,   0,   0,   0,   0,   0,  -5],
+    [ -5,   0,   0,   0,   0,   0,   0,  -5],
+    [ -5,   0,   0,   0,   0,   0,   0,  -5],
+    [ -5,   0,   0,   0,   0,   0,   0,  -5],
+    [  0,   0,   0,   5,   5,   0,   0,   0],
+];
+#[rustfmt::skip]
+const QUEEN: Table = [
+    [-20, -10, -10,  -5,  -5, -10, -10, -20],
+    [-10,   0,   0,   0,   0,   0,   0, -10],
+    [-10,   0,   5,   5,   5,   5,   0, -10],
+    [ -5,   0,   5,   5,   5,   5,   0,  -5],
+    [  0,   0,   5,   5,   5,   5,   0,  -5],
+    [-10,   5,   5,   5,   5,   5,   0, -10],
+    [-10,   0,   5,   0,   0,   0,   0, -10],
+    [-20, -10, -10,  -5,  -5, -10, -10, -20],
+];
+#[rustfmt::skip]
+const KING_MG: Table = [
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-20, -30, -30, -40, -40, -30, -30, -20],
+    [-10, -20, -20, -20, -20, -20, -20, -10],
+    [ 20,  20,   0,   0,   0,   0,  20,  20],
+    [ 20,  30,  10,   0,   0,  10,  30,  20],
+];
+#[rustfmt::skip]
+const KING_EG: Table = [
+    [-50, -40, -30, -20, -20, -30, -40, -50],
+    [-30, -20, -10,   0,   0, -10, -20, -30],
+    [-30, -10,  20,  30,  30,  20, -10, -30],
+    [-30, -10,  30,  40,  40,  30, -10, -30],
+    [-30, -10,  30,  40,  40,  30, -10, -30],
+    [-30, -10,  20,  30,  30,  20, -10, -30],
+    [-30, -30,   0,   0,   0,   0, -30, -30],
+    [-50, -30, -30, -30, -30, -30, -30, -50],
+];
+
+// Bonus of a passed pawn by how many rows it has walked
+const PASSED_MG: [i32; 7] = [0, 5, 10, 20, 35, 60, 100];
+const PASSED_EG: [i32; 7] = [0, 10, 20, 40, 70, 110, 160];
+
+const DOUBLED: Score = Score(-10, -25); // For every extra pawn on a file
+const ISOLATED: Score = Score(-15, -20); // No friendly pawn on the files next to it
+const BISHOP_PAIR: Score = Score(30, 50);
+const MISSING_SHIELD: i32 = -15; // Middlegame only, pawn missing in front of the king
+const KING_ZONE_ATTACK: i32 = -10; // Middlegame only, square next to the king attacked
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Score(i32, i32); // (middlegame, endgame)
+
+impl Score {
+    fn add(&mut self, other: Score) {
+        self.0 += other.0;
+        self.1 += other.1;
+    }
+
+    fn times(self, n: i32) -> Score {
+        Score(self.0 * n, self.1 * n)
+    }
+
+    fn taper(self, phase: i32) -> i32 {
+        (self.0 * phase + self.1 * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+}
+
+/// One part of the evaluation, in centipawns for each side.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Term {
+    pub name: &'static str,
+    pub white: i32,
+    pub black: i32,
+}
+
+impl Term {
+    pub fn total(&self) -> i32 {
+        self.white - self.black
+    }
+}
+
+/// Evaluation of a position broken down term by term.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evaluation {
+    pub terms: [Term; 6],
+    pub phase: i32, // 24 at the start, 0 when only kings and pawns are left
+}
+
+impl Evaluation {
+    /// Score of the position in centipawns, positive is good for white.
+    pub fn total(&self) -> i32 {
+        self.terms.iter().map(Term::total).sum()
+    }
+}
+
+impl Display for Evaluation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<16}{:>8}{:>8}{:>8}",
+            "Term", "White", "Black", "Total"
+        )?;
+        for term in &self.terms {
+            writeln!(
+                f,
+                "{:<16}{:>8}{:>8}{:>8}",
+                term.name,
+                term.white,
+                term.black,
+                term.total()
+            )?;
+        }
+        writeln!(f, "Phase: {}/{}", self.phase, MAX_PHASE)?;
+        write!(f, "Total: {:+.2} pawns", self.total() as f64 / 100.0)
+    }
+}
+
+fn material(piece: char) -> Score {
+    match piece.to_ascii_lowercase() {
+        'p' => Score(100, 120),
+        'n' => Score(320, 300),
+        'b' => Score(330, 320),
+        'r' => Score(500, 550),
+        'q' => Score(950, 1000),
+        _ => Score(0, 0),
+    }
+}
+
+fn phase_of(piece: char) -> i32 {
+    match piece.to_ascii_lowercase() {
+        'n' | 'b' => 1,
+        'r' => 2,
+        'q' => 4,
+        _ => 0,
+    }
+}
+
+fn piece_square(piece: char, x: usize, y: usize) -> Score {
+    // Black looks at the tables upside down
+    let row = if is_white(piece) { y - 1 } else { 8 - y };
+    let column = x - 1;
+    let same = |table: &Table| Score(table[row][column], table[row][column]);
+    match piece.to_ascii_lowercase() {
+        'p' => Score(PAWN_MG[row][column], PAWN_EG[row][column]),
+        'n' => same(&KNIGHT),
+        'b' => same(&BISHOP),
+        'r' => same(&ROOK),
+        'q' => same(&QUEEN),
+        'k' => Score(KING_MG[row][column], KING_EG[row][column]),
+        _ => Score(0, 0),
+    }
+}
+
+// Bonus or penalty compared to an average number of reachable squares
+fn mobility(bitboards: &Bitboards, piece: char, index: usize) -> Score {
+    // Squares the piece attacks which aren't taken by its own side
+    let own = bitboards.colors[side(is_white(piece))];
+    let reach = (bitboards.attacks(piece, index) & !own).count_ones() as i32;
+    match piece.to_ascii_lowercase() {
+        'n' => Score(4, 4).times(reach - 4),
+        'b' => Score(5, 5).times(reach - 7),
+        'r' => Score(2, 4).times(reach - 7),
+        'q' => Score(1, 2).times(reach - 14),
+        _ => Score(0, 0),
+    }
+}
+
+fn pawn_structure(grid: &Grid, white: bool) -> Score {
+    let pawn = if white { 'P' } else { 'p' };
+    let enemy = if white { 'p' } else { 'P' };
+    let pawns_on =
+        |piece: char, file: usize| (0..8).filter(|&row| grid[row][file] == piece).count();
+
+    let mut score = Score(0, 0);
+    for file in 0..8 {
+        let count = pawns_on(pawn, file) as i32;
+        if count > 1 {
+            score.add(DOUBLED.times(count - 1));
+        }
+        let neighbours = [file.wrapping_sub(1), file + 1]
+            .into_iter()
+            .filter(|&f| f < 8)
+            .map(|f| pawns_on(pawn, f))
+            .sum::<usize>();
+        if count > 0 && neighbours == 0 {
+            score.add(ISOLATED.times(count));
+        }
+    }
+
+    for (row, squares) in grid.iter().enumerate() {
+        for (file, &piece) in squares.iter().enumerate() {
+            // A pawn on its own back rank can only come from a board edited by hand
+            let back_rank = if white { 7 } else { 0 };
+            if piece != pawn || row == back_rank {
+                continue;
+            }
+            // Rows in front of the pawn, towards the promotion square
+            let ahead: Vec<usize> = if white {
+                (0..row).collect()
+            } else {
+                (row + 1..8).collect()
+            };
+            let blocked = ahead.iter().any(|&r| {
+                (file.saturating_sub(1)..=(file + 1).min(7)).any(|f| grid[r][f] == enemy)
+            });
+            if !blocked {
+                let walked = if white { 6 - row } else { row - 1 };
+                score.add(Score(PASSED_MG[walked], PASSED_EG[walked]));
+            }
+        }
+    }
+    score
+}
+
+fn king_safety(grid: &Grid, bitboards: &Bitboards, white: bool) -> Score {
+    let king = match bitboards.king(white) {
+        Some(king) => bitboard::square(king),
+        None => return Score(0, 0),
+    };
+    let (x, y) = (king[0] as i8, king[1] as i8);
+    let mut penalty = 0;
+
+    // Pawns in front of a castled king keep it safe
+    let back_rows = if white { [8, 7] } else { [1, 2] };
+    if back_rows.contains(&king[1]) {
+        let (pawn, step) = if white { ('P', -1) } else { ('p', 1) };
+        for file in x - 1..=x + 1 {
+            let shielded = [y + step, y + 2 * step]
+                .iter()
+                .any(|&row| at(grid, file, row) == Some(pawn));
+            if (1..=8).contains(&file) && !shielded {
+                penalty += MISSING_SHIELD;
+            }
+        }
+    }
+
+    // Enemy pieces looking at the squares around the king
+    let king = bitboard::index(king);
+    for zone in squares(KING_ATTACKS[king] | bit(king)) {
+        if bitboards.is_attacked(zone, !white) {
+            penalty += KING_ZONE_ATTACK;
+        }
+    }
+    Score(penalty, 0)
+}
+
+impl Board {
+    /// Positional evaluation of the board, see Evaluation for the terms.
+    pub fn evaluation(&self) -> Evaluation {
+        let grid = &self.board;
+        // [material, piece squares, mobility, pawn structure, king safety, bishop pair]
+        let mut scores = [[Score(0, 0); 2]; 6]; // [term][white, black]
+        let mut bishops = [0, 0];
+        let mut phase = 0;
+
+        for (row, squares) in grid.iter().enumerate() {
+            for (column, &piece) in squares.iter().enumerate() {
+                if piece == ' ' {
+                    continue;
+                }
+                let side = side(is_white(piece));
+                let (x, y) = (column + 1, row + 1);
+                scores[0][side].add(material(piece));
+                scores[1][side].add(piece_square(piece, x, y));
+                scores[2][side].add(mobility(&self.bitboards, piece, row * 8 + column));
+                if piece.eq_ignore_ascii_case(&'b') {
+                    bishops[side] += 1;
+                }
+                phase += phase_of(piece);
+            }
+        }
+        for (side, white) in [(0, true), (1, false)] {
+            scores[3][side] = pawn_structure(grid, white);
+            scores[4][side] = king_safety(grid, &self.bitboards, white);
+            if bishops[side] >= 2 {
+                scores[5][side] = BISHOP_PAIR;
+            }
+        }
+
+        let phase = phase.min(MAX_PHASE);
+        let names = [
+            "Material",
+            "Piece squares",
+            "Mobility",
+            "Pawn structure",
+            "King safety",
+            "Bishop pair",
+        ];
+        Evaluation {
+            terms: std::array::from_fn(|i| Term {
+                name: names[i],
+                white: scores[i][0].taper(phase),
+                black: scores[i][1].taper(phase),
+            }),
+            phase,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(fen: &str) -> Evaluation {
+        Board::from_fen(fen).unwrap().evaluation()
+    }
+
+    fn term(fen: &str, name: &str) -> Term {
+        *evaluate(fen).terms.iter().find(|t| t.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_start_position_is_equal() {
+        let evaluation = Board::default().evaluation();
+        assert_eq!(evaluation.phase, MAX_PHASE);
+        assert_eq!(evaluation.total(), 0);
+        for term in evaluation.terms {
+            assert_eq!(term.white, term.black, "{}", term.name);
+        }
+    }
+
+    #[test]
+    fn test_mirrored_positions() {
+        // Same position with colors swapped and the board upside down
+        let fens = [
+            (
+                "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+                "rnbqkb1r/pppp1ppp/5n2/4p3/4P3/2N5/PPPP1PPP/R1BQKBNR b KQkq - 2 3",
+            ),
+            (
+                "8/5k2/3p4/1p6/1P3P2/8/6K1/8 w - - 0 1",
+                "8/6k1/8/1p3p2/1P6/3P4/5K2/8 b - - 0 1",
+            ),
+        ];
+        for (fen, mirrored) in fens {
+            assert_eq!(
+                evaluate(fen).total(),
+                -evaluate(mirrored).total(),
+                "{}",
+                fen
+            );
+        }
+    }
+
+    #[test]
+    fn test_pawn_structure() {
+        // Doubled and isolated pawns on the c file against a healthy pair
+        let bad = term("4k3/pp6/8/8/8/2P5/2P5/4K3 w - - 0 1", "Pawn structure");
+        assert!(bad.white < 0);
+        // Passed pawns are worth more the further they are
+        let far = term("4k3/8/1P6/8/8/8/8/4K3 w - - 0 1", "Pawn structure");
+        let near = term("4k3/8/8/8/8/1P6/8/4K3 w - - 0 1", "Pawn structure");
+        assert!(far.white > near.white);
+        // A pawn in front of it stops a pawn from being passed
+        let blocked = term("4k3/2p5/8/8/8/1P6/8/4K3 w - - 0 1", "Pawn structure");
+        assert!(blocked.white < near.white);
+
+        // Pawns on their own back rank aren't passed, and don't break the evaluation
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        board.board[7][0] = 'P';
+        board.board[0][7] = 'p';
+        board.encode();
+        let evaluation = board.evaluation();
+        let pawns = evaluation.terms.iter().find(|t| t.name == "Pawn structure");
+        assert_eq!(pawns.unwrap().white, pawns.unwrap().black);
+    }
+
+    #[test]
+    fn test_bishop_pair_and_phase() {
+        let evaluation = evaluate("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1");
+        assert_eq!(evaluation.phase, 2);
+        assert_eq!(evaluation.terms[5].white, BISHOP_PAIR.taper(2));
+        assert_eq!(evaluation.terms[5].black, 0);
+    }
+
+    #[test]
+    fn test_king_safety() {
+        let castled = term("r5k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1", "King safety");
+        assert_eq!(castled.total(), 0);
+        // White king lost its pawns and is looked at by the queen
+        let open = term("r5k1/q4ppp/8/8/8/8/8/R5K1 w - - 0 1", "King safety");
+        assert!(open.white < castled.white);
+    }
+
+    #[test]
+    fn test_mobility() {
+        let center = term("4k3/8/8/3N4/8/8/8/4K3 w - - 0 1", "Mobility");
+        let corner = term("4k3/8/8/8/8/8/8/N3K3 w - - 0 1", "Mobility");
+        assert!(center.white > corner.white);
+    }
+}
@@ -0,0 +1,436 @@
+/*
+ * File: fen.rs
+ * Purpose: Reading and writing standard Forsyth-Edwards Notation
+ * Date: 18.10.2026
+ * */
+
+/* A standard FEN has 6 fields separated by spaces:
+ *
+ *  rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1
+ *  ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ ^ ^^^^ ^^ ^ ^
+ *  piece placement                               | |    |  | fullmove number
+ *                                   side to move-' |    |  '-halfmove clock
+ *                                  castling rights-'    '-en passant square
+ *
+ * Ranks are written from the 8th to the 1st and separated by '/',
+ * digits stand for that many empty squares.
+ * The internal FEN of Board is the same placement with the digits
+ * expanded to whitespaces and without the '/' separators.
+ *
+ * Chess960 positions need to tell which rook can castle:
+ *  X-FEN          KQkq is the outermost rook of that side, a file letter
+ *                 (A-H for white, a-h for black) names any other rook
+ *  Shredder-FEN   always the file letters, like HAha
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use super::castling::{back_row, CastlingRights};
+use super::chess_move::{parse_square, square_name};
+use super::{nums_to_whitespaces, Board};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+#[allow(dead_code)]
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FenErr(pub String);
+
+impl Display for FenErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid FEN: {}", self.0)
+    }
+}
+
+impl Error for FenErr {}
+
+fn parse_placement(placement: &str) -> Result<String, FenErr> {
+    let ranks: Vec<&str> = placement.split('/').collect();
+    if ranks.len() != 8 {
+        return Err(FenErr(format!("expected 8 ranks, found {}", ranks.len())));
+    }
+    let mut kings = [0, 0]; // [white, black]
+    for (i, rank) in ranks.iter().enumerate() {
+        let mut squares = 0;
+        for ch in rank.chars() {
+            match ch {
+                '1'..='8' => squares += ch.to_digit(10).unwrap() as usize,
+                // Pawns promote on the last rank and can't go back to their own
+                'p' | 'P' if i == 0 || i == 7 => {
+                    return Err(FenErr(format!("pawn on rank {}", 8 - i)))
+                }
+                'K' | 'k' => {
+                    kings[usize::from(ch == 'k')] += 1;
+                    squares += 1
+                }
+                'p' | 'n' | 'b' | 'r' | 'q' | 'P' | 'N' | 'B' | 'R' | 'Q' => squares += 1,
+                _ => return Err(FenErr(format!("unknown piece '{}' on rank {}", ch, 8 - i))),
+            }
+        }
+        if squares != 8 {
+            return Err(FenErr(format!(
+                "rank {} has {} squares instead of 8",
+                8 - i,
+                squares
+            )));
+        }
+    }
+    for (side, count) in ["white", "black"].iter().zip(kings) {
+        if count != 1 {
+            return Err(FenErr(format!("{} has {} kings instead of 1", side, count)));
+        }
+    }
+    Ok(nums_to_whitespaces(&ranks.concat()))
+}
+
+// Files of the king and the rooks on the back rank of the side
+fn back_rank(grid: &[[char; 8]; 8], white: bool) -> (Option<usize>, Vec<usize>) {
+    let rank = &grid[back_row(white) - 1];
+    let find = |piece: char| {
+        let piece = if white {
+            piece.to_ascii_uppercase()
+        } else {
+            piece
+        };
+        (1..=8).filter(move |&file| rank[file - 1] == piece)
+    };
+    (find('k').next(), find('r').collect())
+}
+
+fn parse_castling(field: &str, grid: &[[char; 8]; 8]) -> Result<CastlingRights, FenErr> {
+    let mut rights = CastlingRights::none();
+    if field == "-" {
+        return Ok(rights);
+    }
+    for ch in field.chars() {
+        let white = ch.is_ascii_uppercase();
+        let (king, rooks) = back_rank(grid, white);
+        let (king_side, rook_file) = match ch.to_ascii_lowercase() {
+            // Outermost rook, or the normal square if there is none (the right is useless then)
+            'k' => (
+                true,
+                rooks
+                    .last()
+                    .copied()
+                    .filter(|&f| Some(f) > king)
+                    .unwrap_or(8),
+            ),
+            'q' => (
+                false,
+                rooks
+                    .first()
+                    .copied()
+                    .filter(|&f| Some(f) < king)
+                    .unwrap_or(1),
+            ),
+            file @ 'a'..='h' => {
+                let file = (file as u8 - b'a') as usize + 1;
+                match king {
+                    Some(king) if king != file => (file > king, file),
+                    _ => {
+                        return Err(FenErr(format!(
+                            "castling right '{}' needs a king on the back rank",
+                            ch
+                        )))
+                    }
+                }
+            }
+            _ => return Err(FenErr(format!("unknown castling right '{}'", ch))),
+        };
+        if rights.allows(white, king_side) {
+            return Err(FenErr(format!("castling right '{}' is repeated", ch)));
+        }
+        rights.grant(white, king_side, rook_file);
+    }
+    Ok(rights)
+}
+
+// A position with castling rights needs Chess960 rules if the king or a rook isn't where it starts normally
+fn needs_chess960(field: &str, rights: &CastlingRights, grid: &[[char; 8]; 8]) -> bool {
+    let moved = [true, false].iter().any(|&white| {
+        let (king, _) = back_rank(grid, white);
+        let castles = rights.allows(white, true) || rights.allows(white, false);
+        (rights.allows(white, true) && rights.rook_file(white, true) != 8)
+            || (rights.allows(white, false) && rights.rook_file(white, false) != 1)
+            || (castles && king.is_some() && king != Some(5))
+    });
+    moved || field.chars().any(|ch| !"KQkq-".contains(ch))
+}
+
+fn parse_en_passant(field: &str, white: bool) -> Result<Option<[usize; 2]>, FenErr> {
+    if field == "-" {
+        return Ok(None);
+    }
+    let square = parse_square(field).ok_or(FenErr(format!("'{}' is not a square", field)))?;
+    // The skipped square is on the 6th rank when white is to move, 3rd otherwise
+    let row = if white { 3 } else { 6 };
+    if square[1] != row {
+        return Err(FenErr(format!(
+            "'{}' can't be an en passant square for {} to move",
+            field,
+            if white { "white" } else { "black" }
+        )));
+    }
+    Ok(Some(square))
+}
+
+fn parse_number(field: Option<&str>, name: &str, default: u32) -> Result<u32, FenErr> {
+    match field {
+        Some(number) => number
+            .parse()
+            .map_err(|_| FenErr(format!("{} '{}' is not a number", name, number))),
+        None => Ok(default),
+    }
+}
+
+// Opposite of nums_to_whitespaces, used for a rank of the board
+fn whitespaces_to_nums(rank: &[char; 8]) -> String {
+    let mut s = String::with_capacity(8);
+    let mut empty = 0;
+    for &ch in rank {
+        if ch == ' ' {
+            empty += 1;
+            continue;
+        }
+        if empty > 0 {
+            s.push_str(&empty.to_string());
+            empty = 0;
+        }
+        s.push(ch);
+    }
+    if empty > 0 {
+        s.push_str(&empty.to_string());
+    }
+    s
+}
+
+impl Board {
+    #[allow(dead_code)]
+    pub fn from_fen(fen: &str) -> Result<Board, FenErr> {
+        let mut board = Board::default();
+        board.set_fen(fen)?;
+        Ok(board)
+    }
+
+    /// Loads the position from a FEN, colors of the board are kept as they are
+    /// and the move history is cleared. Halfmove clock and fullmove number can be left out.
+    pub fn set_fen(&mut self, fen: &str) -> Result<(), FenErr> {
+        self.set_fen_chess960(fen, false)
+    }
+
+    /// Same as set_fen, `chess960` asks for Chess960 castling even if the FEN
+    /// doesn't need it (UCI_Chess960, xboard's variant or a PGN Variant tag).
+    pub fn set_fen_chess960(&mut self, fen: &str, chess960: bool) -> Result<(), FenErr> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() < 4 || fields.len() > 6 {
+            return Err(FenErr(format!(
+                "expected 6 fields separated by spaces, found {}",
+                fields.len()
+            )));
+        }
+
+        let placement = parse_placement(fields[0])?;
+        let turn = match fields[1] {
+            "w" => true,
+            "b" => false,
+            side => {
+                return Err(FenErr(format!(
+                    "side to move has to be 'w' or 'b', not '{}'",
+                    side
+                )))
+            }
+        };
+        let grid: Vec<char> = placement.chars().collect();
+        let mut rows = [[' '; 8]; 8];
+        for (i, row) in rows.iter_mut().enumerate() {
+            row.copy_from_slice(&grid[i * 8..i * 8 + 8]);
+        }
+        let castling = parse_castling(fields[2], &rows)?;
+        let en_passant = parse_en_passant(fields[3], turn)?;
+        let halfmove_clock = parse_number(fields.get(4).copied(), "halfmove clock", 0)?;
+        let fullmove_number = parse_number(fields.get(5).copied(), "fullmove number", 1)?;
+        if fullmove_number == 0 {
+            return Err(FenErr("fullmove number starts from 1".to_owned()));
+        }
+
+        self.FEN = placement;
+        self.decode();
+        self.turn = turn;
+        // Only this position decides, not the game played before
+        self.chess960 = chess960 || needs_chess960(fields[2], &castling, &rows);
+        self.castling = castling;
+        self.en_passant = en_passant;
+        self.halfmove_clock = halfmove_clock;
+        self.fullmove_number = fullmove_number;
+        self.hash = self.compute_hash();
+        // Moves played before belong to another position
+        self.history.clear();
+        self.redo.clear();
+        Ok(())
+    }
+
+    // KQkq, or X-FEN with file letters for rooks which aren't the outermost ones
+    fn castling_field(&self) -> String {
+        if !self.chess960 {
+            return self.castling.to_string();
+        }
+        let mut field = String::new();
+        for (white, king_side, letter) in [
+            (true, true, 'K'),
+            (true, false, 'Q'),
+            (false, true, 'k'),
+            (false, false, 'q'),
+        ] {
+            if !self.castling.allows(white, king_side) {
+                continue;
+            }
+            let file = self.castling.rook_file(white, king_side);
+            let (_, rooks) = back_rank(&self.board, white);
+            let outermost = match king_side {
+                true => rooks.last(),
+                false => rooks.first(),
+            };
+            field.push(match outermost == Some(&file) {
+                true => letter,
+                false if white => (b'A' + file as u8 - 1) as char,
+                false => (b'a' + file as u8 - 1) as char,
+            });
+        }
+        if field.is_empty() {
+            field.push('-');
+        }
+        field
+    }
+
+    pub fn to_fen(&self) -> String {
+        let placement: Vec<String> = self.board.iter().map(whitespaces_to_nums).collect();
+        format!(
+            "{} {} {} {} {} {}",
+            placement.join("/"),
+            if self.turn { "w" } else { "b" },
+            self.castling_field(),
+            match self.en_passant {
+                Some(square) => square_name(square),
+                None => "-".to_owned(),
+            },
+            self.halfmove_clock,
+            self.fullmove_number
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_starting_fen() {
+        assert_eq!(Board::default().to_fen(), STARTING_FEN);
+        assert_eq!(Board::from_fen(STARTING_FEN).unwrap(), Board::default());
+    }
+
+    #[test]
+    fn test_fen_round_trip() {
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "4k3/8/8/8/8/8/8/4K3 b Kq - 37 80",
+        ];
+        for fen in fens {
+            assert_eq!(Board::from_fen(fen).unwrap().to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn test_fen_after_moves() {
+        let mut board = Board::default();
+        board
+            .move_piece(crate::board::Move::new([5, 7], [5, 5]))
+            .unwrap(); // e4
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+        board
+            .move_piece(crate::board::Move::new([7, 1], [6, 3]))
+            .unwrap(); // Nf6
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkb1r/pppppppp/5n2/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 1 2"
+        );
+    }
+
+    #[test]
+    fn test_chess960_castling_fields() {
+        // Shredder-FEN comes back as X-FEN
+        let fen = "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9";
+        let board = Board::from_fen(fen).unwrap();
+        assert!(board.chess960);
+        assert_eq!(board.castling.rook_file(true, false), 6);
+        assert_eq!(
+            board.to_fen(),
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9"
+        );
+
+        // A rook which isn't the outermost one keeps its file letter
+        let fen = "rk2r2r/8/8/8/8/8/8/RK2R2R w EQeq - 0 1";
+        let board = Board::from_fen(fen).unwrap();
+        assert_eq!(board.castling.rook_file(true, true), 5);
+        assert_eq!(board.to_fen(), fen);
+
+        assert!(!Board::from_fen(STARTING_FEN).unwrap().chess960);
+        // A normal position after a Chess960 game castles normally again
+        let mut board = Board::chess960(0).unwrap();
+        board.set_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        assert!(!board.chess960);
+        board
+            .move_piece(crate::board::Move::new([5, 8], [7, 8]))
+            .unwrap();
+        assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/5RK1 b - - 1 1");
+        board
+            .set_fen_chess960("4k3/8/8/8/8/8/8/4K2R w K - 0 1", true)
+            .unwrap();
+        assert!(board.chess960);
+        assert!(Board::from_fen("4k3/8/8/8/8/8/8/4K2R w A - 0 1").is_ok());
+        assert!(Board::from_fen("4k3/8/8/8/8/8/4K3/7R w H - 0 1").is_err());
+    }
+
+    #[test]
+    fn test_short_fen() {
+        let board = Board::from_fen("8/8/8/8/8/8/8/K6k w - -").unwrap();
+        assert_eq!(board.to_fen(), "8/8/8/8/8/8/8/K6k w - - 0 1");
+    }
+
+    #[test]
+    fn test_invalid_fen() {
+        let invalid = [
+            "",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNX w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkk - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e4 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e3 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - x 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 0",
+            "4k3/8/8/8/8/8/8/P3K3 w - - 0 1", // Pawn on the 1st rank
+            "4k2p/8/8/8/8/8/8/4K3 w - - 0 1", // Pawn on the 8th rank
+            "8/8/8/8/8/8/8/4K3 w - - 0 1",    // No black king
+            "4k3/8/8/8/8/8/8/3KK3 w - - 0 1", // Two white kings
+        ];
+        for fen in invalid {
+            assert!(Board::from_fen(fen).is_err(), "{}", fen);
+        }
+        let err = Board::from_fen("4k3/8/8/8/8/8/8/8 w - - 0 1").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid FEN: white has 0 kings instead of 1"
+        );
+        let err = Board::from_fen("8/8/8/8/8/8/8/7 w - - 0 1").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid FEN: rank 1 has 7 squares instead of 8"
+        );
+    }
+}
@@ -0,0 +1,58 @@
+/*
+ * File: game_result.rs
+ * Purpose: Contains the GameResult enum which tells how a game ended
+ * Date: 18.10.2026
+ * */
+
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    Checkmate(bool), // Winner of the game -> true for white
+    Stalemate,
+    InsufficientMaterial, // Dead position, nobody can checkmate anymore
+    SeventyFiveMoves,     // Ends the game without anyone claiming it
+    FivefoldRepetition,   // Ends the game without anyone claiming it
+    FiftyMoves,           // Has to be claimed by a player
+    ThreefoldRepetition,  // Has to be claimed by a player
+}
+
+impl GameResult {
+    /// Returns the winner (true for white), None if the game is drawn.
+    pub fn winner(&self) -> Option<bool> {
+        match self {
+            GameResult::Checkmate(white) => Some(*white),
+            _ => None,
+        }
+    }
+
+    /// Result as it is written in PGN.
+    pub fn score(&self) -> &'static str {
+        match self.winner() {
+            Some(true) => "1-0",
+            Some(false) => "0-1",
+            None => "1/2-1/2",
+        }
+    }
+}
+
+impl Display for GameResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameResult::Checkmate(true) => write!(f, "Checkmate! White wins"),
+            GameResult::Checkmate(false) => write!(f, "Checkmate! Black wins"),
+            GameResult::Stalemate => write!(f, "Stalemate! It's a draw"),
+            GameResult::InsufficientMaterial => {
+                write!(f, "Insufficient material! It's a draw")
+            }
+            GameResult::SeventyFiveMoves => {
+                write!(f, "75 moves without a capture or pawn move! It's a draw")
+            }
+            GameResult::FivefoldRepetition => write!(f, "Fivefold repetition! It's a draw"),
+            GameResult::FiftyMoves => {
+                write!(f, "50 moves without a capture or pawn move! It's a draw")
+            }
+            GameResult::ThreefoldRepetition => write!(f, "Threefold repetition! It's a draw"),
+        }
+    }
+}
@@ -0,0 +1,181 @@
+/*
+ * File: history.rs
+ * Purpose: Keeping the played moves of a Board so they can be undone and redone
+ * Date: 18.10.2026
+ * */
+
+/* Every played move pushes a HistoryEntry which knows everything the move
+ * destroyed: the taken piece, castling rights, en passant square, clocks and
+ * the Zobrist key.
+ * Undoing pops the entry and puts all of them back, the undone move goes to
+ * the redo stack until a new move is played.
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use super::castling::CastlingRights;
+use super::chess_move::{Move, MoveType};
+use super::Board;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub played: Move,
+    pub move_type: MoveType,
+    pub moved: char, // Piece which moved (still a pawn for promotions)
+    pub taken: char, // Taken piece, ' ' if nothing was taken
+    pub castling: CastlingRights,
+    pub en_passant: Option<[usize; 2]>,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+    pub hash: u64, // Zobrist key before the move
+}
+
+impl Board {
+    /// Takes back the last move, returns it or None if nothing was played.
+    pub fn undo_move(&mut self) -> Option<Move> {
+        let entry = self.unmake_move()?;
+        let played = entry.played.clone();
+        self.redo.push(entry);
+        Some(played)
+    }
+
+    /// Plays the last undone move again.
+    pub fn redo_move(&mut self) -> Option<MoveType> {
+        let entry = self.redo.pop()?;
+        Some(self.make_move(&entry.played))
+    }
+
+    /// Number of half moves which can be undone.
+    #[allow(dead_code)]
+    pub fn ply(&self) -> usize {
+        self.history.len()
+    }
+
+    /* Opposite of make_move. Unlike undo_move it doesn't touch the redo
+     * stack, so it can be used to look ahead without side effects. */
+    pub(crate) fn unmake_move(&mut self) -> Option<HistoryEntry> {
+        let entry = self.history.pop()?;
+        let (from, to) = entry.played.decode_move();
+
+        match entry.move_type {
+            MoveType::EnPassant => {
+                // Taken pawn was next to the moved pawn, not on the target square
+                self.set_square(to, ' ');
+                self.set_square([to[0], from[1]], entry.taken);
+            }
+            MoveType::Castle => {
+                // The king takes its rook in Chess960, so `to` may be the rook's square
+                let white = entry.moved.is_ascii_uppercase();
+                let (rook_from, king_to, rook_to) = entry.castling.squares(white, to[0] > from[0]);
+                let rook = self.board[rook_to[1] - 1][rook_to[0] - 1];
+                self.set_square(king_to, ' ');
+                self.set_square(rook_to, ' ');
+                self.set_square(rook_from, rook);
+            }
+            _ => self.set_square(to, entry.taken),
+        }
+        self.set_square(from, entry.moved);
+
+        self.castling = entry.castling;
+        self.en_passant = entry.en_passant;
+        self.halfmove_clock = entry.halfmove_clock;
+        self.fullmove_number = entry.fullmove_number;
+        self.hash = entry.hash;
+        self.turn = !self.turn;
+        Some(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(board: &mut Board, moves: &[([usize; 2], [usize; 2])]) {
+        for (from, to) in moves {
+            board.move_piece(Move::new(*from, *to)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_undo_to_start() {
+        let mut board = Board::default();
+        play(
+            &mut board,
+            &[
+                ([5, 7], [5, 5]), // e4
+                ([4, 2], [4, 4]), // d5
+                ([5, 5], [4, 4]), // exd5
+                ([4, 1], [4, 4]), // Qxd5
+                ([7, 8], [6, 6]), // Nf3
+            ],
+        );
+        assert_eq!(board.ply(), 5);
+        while board.undo_move().is_some() {}
+        assert_eq!(board.to_fen(), Board::default().to_fen());
+        assert!(board.undo_move().is_none());
+    }
+
+    #[test]
+    fn test_undo_special_moves() {
+        let fen = "r3k2r/8/8/8/4p3/8/3P4/R3K2R w KQkq - 3 10";
+        let mut board = Board::from_fen(fen).unwrap();
+        play(
+            &mut board,
+            &[
+                ([4, 7], [4, 5]), // d4
+                ([5, 5], [4, 6]), // exd3 en passant
+                ([5, 8], [3, 8]), // O-O-O
+                ([8, 1], [8, 8]), // Rxh1+
+            ],
+        );
+        for _ in 0..4 {
+            board.undo_move().unwrap();
+        }
+        assert_eq!(board.to_fen(), fen);
+    }
+
+    #[test]
+    fn test_undo_promotion() {
+        let fen = "r3k3/1P6/8/8/8/8/8/4K3 w q - 0 1";
+        let mut board = Board::from_fen(fen).unwrap();
+        let promotion = Move::new([2, 2], [1, 1]).with_promotion('n');
+        board.move_piece(promotion).unwrap(); // bxa8=N
+        assert_eq!(board.board[0][0], 'N');
+
+        board.undo_move();
+        assert_eq!(board.to_fen(), fen);
+
+        // Redo remembers which piece was chosen
+        board.redo_move();
+        assert_eq!(board.board[0][0], 'N');
+        assert_eq!(board.to_fen(), "N3k3/8/8/8/8/8/8/4K3 b - - 0 1");
+    }
+
+    #[test]
+    fn test_redo() {
+        let mut board = Board::default();
+        play(&mut board, &[([5, 7], [5, 5]), ([5, 2], [5, 4])]);
+        let fen = board.to_fen();
+
+        board.undo_move();
+        board.undo_move();
+        assert_eq!(board.redo_move(), Some(MoveType::DoublePawn));
+        assert_eq!(board.redo_move(), Some(MoveType::DoublePawn));
+        assert_eq!(board.redo_move(), None);
+        assert_eq!(board.to_fen(), fen);
+
+        // A new move forgets the undone moves
+        board.undo_move();
+        play(&mut board, &[([4, 2], [4, 4])]);
+        assert_eq!(board.redo_move(), None);
+    }
+
+    #[test]
+    fn test_boards_are_independent() {
+        let mut first = Board::default();
+        let mut second = Board::default();
+        play(&mut first, &[([5, 7], [5, 5])]);
+        play(&mut second, &[([4, 7], [4, 5])]);
+        first.undo_move();
+        assert_eq!(first.to_fen(), Board::default().to_fen());
+        assert_eq!(second.board[4][3], 'P');
+    }
+}
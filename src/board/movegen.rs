@@ -0,0 +1,315 @@
+/*
+ * File: movegen.rs
+ * Purpose: Generating every legal move of a position
+ * Date: 18.10.2026
+ * */
+
+/* Squares are the same [x, y] pairs Move uses:
+ * x is the file (1 = a, 8 = h) and y is the row counted from the top
+ * of the board (1 = 8th rank, 8 = 1st rank). So white pawns walk towards
+ * y = 1 and black pawns towards y = 8.
+ *
+ * Castling is the king moving two squares (e1g1) in normal chess and the
+ * king taking its own rook (e1h1) in Chess960, where the king may start
+ * next to the g file and a two square move wouldn't tell which move it is.
+ *
+ * Generation happens in two passes:
+ *  1. pseudo legal moves: every move the pieces are physically able to do,
+ *     read from the attack tables of bitboard.rs
+ *  2. legal moves: pseudo legal moves that don't leave the king in check
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use super::bitboard::{self, bit, index, side, squares, Bitboard, Bitboards, PAWN_ATTACKS};
+use super::chess_move::Move;
+use super::{Board, PROMOTED};
+
+pub(super) type Grid = [[char; 8]; 8];
+
+pub(super) const KNIGHT_OFFSETS: [(i8, i8); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+pub(super) const KING_OFFSETS: [(i8, i8); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+pub(super) const ROOK_DIRECTIONS: [(i8, i8); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+pub(super) const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (-1, 1), (-1, -1), (1, -1)];
+
+pub(super) fn at(grid: &Grid, x: i8, y: i8) -> Option<char> {
+    if (1..=8).contains(&x) && (1..=8).contains(&y) {
+        Some(grid[y as usize - 1][x as usize - 1])
+    } else {
+        None // Off the board
+    }
+}
+
+fn square(x: i8, y: i8) -> [usize; 2] {
+    [x as usize, y as usize]
+}
+
+// Same piece letter in the color of the given side
+fn side_piece(piece: char, white: bool) -> char {
+    if white {
+        piece.to_ascii_uppercase()
+    } else {
+        piece.to_ascii_lowercase()
+    }
+}
+
+/// Plays the move on a copy of the bitboards. Only used for looking ahead so
+/// promotions are left as pawns (they can't change whether the king is safe).
+fn play_on_bitboards(board: &Board, current_move: &Move) -> Bitboards {
+    let (from, to) = current_move.decode_move();
+    let piece_on = |square: [usize; 2]| board.board[square[1] - 1][square[0] - 1];
+    let mut next = board.bitboards;
+    let piece = piece_on(from);
+
+    if piece.eq_ignore_ascii_case(&'p') && Some(to) == board.en_passant {
+        // The taken pawn stands next to the moving pawn, not on the target square
+        let taken = [to[0], from[1]];
+        next.remove(index(taken), piece_on(taken));
+    }
+    if let Some(king_side) = board.castling_side(current_move) {
+        // King and rook can land on each other's squares, so both leave first
+        let (rook_from, king_to, rook_to) = board.castling.squares(board.turn, king_side);
+        let rook = piece_on(rook_from);
+        next.remove(index(from), piece);
+        next.remove(index(rook_from), rook);
+        next.put(index(king_to), piece);
+        next.put(index(rook_to), rook);
+        return next;
+    }
+
+    next.remove(index(to), piece_on(to));
+    next.remove(index(from), piece);
+    next.put(index(to), piece);
+    next
+}
+
+impl Board {
+    /// Every move the side to move can legally play in this position.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        self.pseudo_legal_moves()
+            .into_iter()
+            .filter(|current_move| !self.leaves_king_in_check(current_move))
+            .collect()
+    }
+
+    /// Returns true if the square is attacked by the given side.
+    pub fn is_attacked(&self, square: [usize; 2], by_white: bool) -> bool {
+        self.bitboards.is_attacked(index(square), by_white)
+    }
+
+    pub(crate) fn leaves_king_in_check(&self, current_move: &Move) -> bool {
+        let next = play_on_bitboards(self, current_move);
+        match next.king(self.turn) {
+            Some(king) => next.is_attacked(king, !self.turn),
+            None => false,
+        }
+    }
+
+    pub(crate) fn pseudo_legal_moves(&self) -> Vec<Move> {
+        let own = self.bitboards.colors[side(self.turn)];
+        let mut moves = Vec::with_capacity(64);
+        for from in squares(own) {
+            let [x, y] = bitboard::square(from);
+            let piece = self.board[y - 1][x - 1];
+            let targets = match piece.to_ascii_lowercase() {
+                'p' => self.pawn_targets(from),
+                _ => self.bitboards.attacks(piece, from) & !own,
+            };
+            for to in squares(targets) {
+                let to = bitboard::square(to);
+                if piece.eq_ignore_ascii_case(&'p') && (to[1] == 1 || to[1] == 8) {
+                    // Every piece the pawn can turn into is a different move
+                    for promoted in PROMOTED {
+                        moves.push(Move::new([x, y], to).with_promotion(promoted));
+                    }
+                } else {
+                    moves.push(Move::new([x, y], to));
+                }
+            }
+            if piece.eq_ignore_ascii_case(&'k') {
+                self.castling_moves(x as i8, y as i8, &mut moves);
+            }
+        }
+        moves
+    }
+
+    fn pawn_targets(&self, from: usize) -> Bitboard {
+        let white = self.turn;
+        let empty = !self.bitboards.occupied;
+        // White pawns walk towards bit 0, black pawns towards bit 63
+        let forward = |pawns: Bitboard| if white { pawns >> 8 } else { pawns << 8 };
+        let start_row = if white { 7 } else { 2 };
+
+        let single = forward(bit(from)) & empty;
+        let double = match bitboard::square(from)[1] == start_row {
+            true => forward(single) & empty,
+            false => 0,
+        };
+        let en_passant = self.en_passant.map_or(0, |target| bit(index(target)));
+        let enemies = self.bitboards.colors[side(!white)] | en_passant;
+        single | double | (PAWN_ATTACKS[side(white)][from] & enemies)
+    }
+
+    fn castling_moves(&self, x: i8, y: i8, moves: &mut Vec<Move>) {
+        for king_side in [true, false] {
+            if self.castling_error(king_side).is_none() {
+                let (rook_from, king_to, _) = self.castling.squares(self.turn, king_side);
+                let to = if self.chess960 { rook_from } else { king_to };
+                moves.push(Move::new(square(x, y), to));
+            }
+        }
+    }
+
+    /// Side of the castling if the move is one, Some(true) for short castling.
+    pub(crate) fn castling_side(&self, current_move: &Move) -> Option<bool> {
+        let (from, to) = current_move.decode_move();
+        let piece = self.board[from[1] - 1][from[0] - 1];
+        if !piece.eq_ignore_ascii_case(&'k') {
+            return None;
+        }
+        let white = piece.is_ascii_uppercase();
+        let takes_rook = self.board[to[1] - 1][to[0] - 1] == side_piece('r', white);
+        let two_squares = !self.chess960 && from[0].abs_diff(to[0]) == 2;
+        (takes_rook || two_squares).then_some(to[0] > from[0])
+    }
+
+    /// Tells why the side to move can't castle to the given side,
+    /// returns None if castling is possible.
+    pub(crate) fn castling_error(&self, king_side: bool) -> Option<&'static str> {
+        let white = self.turn;
+        if !self.castling.allows(white, king_side) {
+            return Some("King or rook has already moved");
+        }
+        // Rights are kept up to date by make_move, but a FEN can lie
+        let (rook_from, king_to, rook_to) = self.castling.squares(white, king_side);
+        let y = rook_from[1];
+        let king = match self.bitboards.king(white).map(bitboard::square) {
+            Some([x, row]) if row == y && (self.chess960 || x == 5) => [x, y],
+            _ => return Some("King is not on its starting square"),
+        };
+        if at(&self.board, rook_from[0] as i8, y as i8) != Some(side_piece('r', white))
+            || (rook_from[0] > king[0]) != king_side
+        {
+            return Some("Rook is not on its starting square");
+        }
+        // Everything between the outermost squares must be empty but the king and the rook
+        let files = [king[0], king_to[0], rook_from[0], rook_to[0]];
+        let (first, last) = (*files.iter().min().unwrap(), *files.iter().max().unwrap());
+        if (first..=last).any(|file| {
+            ![king[0], rook_from[0]].contains(&file) && self.board[y - 1][file - 1] != ' '
+        }) {
+            return Some("There are pieces between the king and the rook");
+        }
+        if self.is_attacked(king, !white) {
+            return Some("You can't castle out of check");
+        }
+        // The castling rook can't cover a square of the king's way, so it is taken off
+        let mut without = self.bitboards;
+        without.remove(index(king), side_piece('k', white));
+        without.remove(index(rook_from), side_piece('r', white));
+        let (low, high) = (king[0].min(king_to[0]), king[0].max(king_to[0]));
+        if (low..=high).any(|file| without.is_attacked(index([file, y]), !white)) {
+            return Some("King can't pass through or land on an attacked square");
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_grid() -> Grid {
+        [[' '; 8]; 8]
+    }
+
+    fn is_square_attacked(grid: &Grid, target: [usize; 2], by_white: bool) -> bool {
+        Bitboards::from_grid(grid).is_attacked(index(target), by_white)
+    }
+
+    #[test]
+    fn test_start_position_moves() {
+        let board = Board::default();
+        assert_eq!(board.legal_moves().len(), 20);
+    }
+
+    #[test]
+    fn test_square_attacked() {
+        let mut grid = empty_grid();
+        grid[3][3] = 'R'; // d5
+        assert!(is_square_attacked(&grid, [4, 1], true));
+        assert!(is_square_attacked(&grid, [8, 4], true));
+        assert!(!is_square_attacked(&grid, [5, 5], true));
+        assert!(!is_square_attacked(&grid, [4, 1], false));
+
+        grid[1][3] = 'p'; // d7 blocks the rook
+        assert!(!is_square_attacked(&grid, [4, 1], true));
+        assert!(is_square_attacked(&grid, [3, 3], false));
+        assert!(is_square_attacked(&grid, [5, 3], false));
+    }
+
+    #[test]
+    fn test_pinned_piece_can_not_move() {
+        let mut board = Board::from_vec(empty_grid());
+        board.board[7][4] = 'K'; // e1
+        board.board[6][4] = 'N'; // e2
+        board.board[0][4] = 'r'; // e8
+        board.encode();
+        let moves = board.legal_moves();
+        assert!(moves.iter().all(|m| m.decode_move().0 != [5, 7]));
+        assert_eq!(moves.len(), 4); // d1, f1, d2, f2
+    }
+
+    #[test]
+    fn test_castling_moves() {
+        let mut board = Board::from_fen("k7/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        let moves = board.legal_moves();
+        assert!(moves.contains(&Move::new([5, 8], [7, 8])));
+        assert!(moves.contains(&Move::new([5, 8], [3, 8])));
+
+        // Rook on f8 covers f1, so the king can't walk over it
+        board.board[0][5] = 'r';
+        board.encode();
+        let moves = board.legal_moves();
+        assert!(!moves.contains(&Move::new([5, 8], [7, 8])));
+        assert!(moves.contains(&Move::new([5, 8], [3, 8])));
+
+        // Rook on b8 only covers b1 which the king doesn't walk over
+        board.board[0][5] = ' ';
+        board.board[0][1] = 'r';
+        board.encode();
+        assert!(board.legal_moves().contains(&Move::new([5, 8], [3, 8])));
+
+        // Without the rights nothing changes on the board but castling is gone
+        board.castling.white_queen = false;
+        assert!(!board.legal_moves().contains(&Move::new([5, 8], [3, 8])));
+    }
+
+    #[test]
+    fn test_promotion_moves() {
+        let mut board = Board::from_vec(empty_grid());
+        board.board[1][0] = 'P'; // a7
+        board.encode();
+        let promotions: Vec<Move> = ['q', 'r', 'b', 'n']
+            .iter()
+            .map(|&piece| Move::new([1, 2], [1, 1]).with_promotion(piece))
+            .collect();
+        assert_eq!(board.legal_moves(), promotions);
+    }
+}
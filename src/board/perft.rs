@@ -0,0 +1,151 @@
+/*
+ * File: perft.rs
+ * Purpose: Counting the positions reachable in a number of moves to test the move generator
+ * Date: 18.10.2026
+ * */
+
+/* Perft (performance test) walks the whole move tree down to a depth and
+ * counts the leaves. The counts of well known positions are published, so a
+ * wrong count means the move generator has a bug somewhere. Divide prints
+ * the count below every root move, which narrows the bug down to a move.
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use super::chess_move::square_name;
+use super::Board;
+
+impl Board {
+    fn perft_nodes(&mut self, depth: u32) -> u64 {
+        let moves = self.legal_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        let mut nodes = 0;
+        for current_move in moves {
+            self.make_move(&current_move);
+            nodes += self.perft_nodes(depth - 1);
+            self.unmake_move();
+        }
+        nodes
+    }
+
+    /// Number of leaves of the move tree at the depth.
+    pub fn perft(&self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        self.clone().perft_nodes(depth)
+    }
+
+    /// Perft of every legal move (e2e4, e7e8q), so they add up to perft(depth).
+    pub fn divide(&self, depth: u32) -> Vec<(String, u64)> {
+        let mut board = self.clone();
+        let mut counts = Vec::new();
+        for current_move in self.legal_moves() {
+            let (from, to) = current_move.decode_move();
+            let mut name = square_name(from) + &square_name(to);
+            name.extend(current_move.promotion());
+            board.make_move(&current_move);
+            counts.push((name, board.perft(depth.saturating_sub(1))));
+            board.unmake_move();
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Published counts, see https://www.chessprogramming.org/Perft_Results
+    fn perft(fen: &str, counts: &[u64]) {
+        let board = Board::from_fen(fen).unwrap();
+        for (depth, &count) in counts.iter().enumerate() {
+            assert_eq!(
+                board.perft(depth as u32 + 1),
+                count,
+                "{} depth {}",
+                fen,
+                depth + 1
+            );
+        }
+    }
+
+    #[test]
+    fn test_perft_start_position() {
+        perft(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            &[20, 400, 8902, 197281],
+        );
+    }
+
+    #[test]
+    fn test_perft_kiwipete() {
+        perft(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            &[48, 2039, 97862],
+        );
+    }
+
+    #[test]
+    fn test_perft_position_3() {
+        perft(
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            &[14, 191, 2812, 43238],
+        );
+    }
+
+    #[test]
+    fn test_perft_position_4() {
+        perft(
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            &[6, 264, 9467],
+        );
+        // Same position with the colors swapped
+        perft(
+            "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1",
+            &[6, 264, 9467],
+        );
+    }
+
+    #[test]
+    fn test_perft_position_5() {
+        perft(
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            &[44, 1486, 62379],
+        );
+    }
+
+    #[test]
+    fn test_perft_position_6() {
+        perft(
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+            &[46, 2079, 89890],
+        );
+    }
+
+    #[test]
+    fn test_perft_chess960() {
+        perft(
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+            &[21, 528, 12189, 326672],
+        );
+        perft(
+            "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9",
+            &[21, 807, 18002, 667366],
+        );
+        perft(
+            "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9",
+            &[20, 479, 10471, 273318],
+        );
+    }
+
+    #[test]
+    fn test_divide() {
+        let board = Board::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let divide = board.divide(2);
+        let total: u64 = divide.iter().map(|(_, nodes)| nodes).sum();
+        assert_eq!(total, board.perft(2));
+        assert!(divide.contains(&("b7b8n".to_owned(), 4)));
+        assert_eq!(divide.len(), board.perft(1) as usize);
+    }
+}
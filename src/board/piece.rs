@@ -21,9 +21,86 @@ pub mod themes {
     pub const PASTEL: Theme = ((210, 145, 188), (149, 125, 173));
     pub const SOLARIZED: Theme = ((203, 75, 22), (211, 54, 130));
     pub const CHALLENGER: Theme = ((0, 0, 0), (0, 0, 0));
+
+    // Names used by --piece-set
+    pub const NAMED: [(&str, Theme); 8] = [
+        ("regular", REGULAR),
+        ("gruvbox", GRUVBOX),
+        ("darcula", DARCULA),
+        ("hacker", HACKER),
+        ("neon", NEON),
+        ("pastel", PASTEL),
+        ("solarized", SOLARIZED),
+        ("challenger", CHALLENGER),
+    ];
+
+    pub fn find(name: &str) -> Option<Theme> {
+        let name = name.to_lowercase();
+        NAMED
+            .iter()
+            .find(|(named, _)| *named == name)
+            .map(|(_, theme)| *theme)
+    }
 }
 
 use std::fmt::{Display, Formatter};
+
+/// How the pieces are drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Glyphs {
+    Ascii,    // Letters, white pieces are upper-case
+    Unicode,  // ♔ for white, ♚ for black
+    Filled,   // ♚ for both, looks better on dark terminals where ♔ seems black
+    NerdFont, // Icons of the patched Nerd Fonts, the same for both
+}
+
+impl Glyphs {
+    // Names used by --glyphs and the glyphs command
+    pub const NAMED: [(&'static str, Glyphs); 4] = [
+        ("ascii", Glyphs::Ascii),
+        ("unicode", Glyphs::Unicode),
+        ("filled", Glyphs::Filled),
+        ("nerd", Glyphs::NerdFont),
+    ];
+
+    pub fn find(name: &str) -> Option<Glyphs> {
+        let name = name.to_lowercase();
+        Glyphs::NAMED
+            .iter()
+            .find(|(named, _)| *named == name)
+            .map(|(_, glyphs)| *glyphs)
+    }
+
+    /// Glyphs which tell the sides apart without colors, Filled and NerdFont
+    /// only differ in color so they become Unicode.
+    pub fn colorless(&self) -> Glyphs {
+        match self {
+            Glyphs::Filled | Glyphs::NerdFont => Glyphs::Unicode,
+            glyphs => *glyphs,
+        }
+    }
+
+    /// Character for the piece of the FEN (' ' for an empty square).
+    pub fn glyph(&self, piece: char) -> char {
+        const KINDS: &str = "kqrbnp";
+        let kind = match KINDS.find(piece.to_ascii_lowercase()) {
+            Some(kind) => kind,
+            None => return piece,
+        };
+        let white = piece.is_ascii_uppercase();
+        match self {
+            Glyphs::Ascii => piece,
+            Glyphs::Unicode if white => "♔♕♖♗♘♙".chars().nth(kind).unwrap(),
+            Glyphs::Unicode | Glyphs::Filled => "♚♛♜♝♞♟".chars().nth(kind).unwrap(),
+            // nf-md-chess_king, queen, rook, bishop, knight and pawn
+            Glyphs::NerdFont => "\u{f0857}\u{f085a}\u{f085b}\u{f085c}\u{f0858}\u{f0859}"
+                .chars()
+                .nth(kind)
+                .unwrap(),
+        }
+    }
+}
+
 pub enum White {
     Pawn,
     Knight,
@@ -41,6 +118,7 @@ pub enum Black {
     King,
 }
 
+#[allow(dead_code)]
 pub enum Piece {
     White(White),
     Black(Black),
@@ -50,22 +128,13 @@ pub enum Piece {
 #[allow(dead_code)]
 impl Piece {
     pub fn is_white(&self) -> bool {
-        match self {
-            Piece::White(_) => true,
-            _ => false,
-        }
+        matches!(self, Piece::White(_))
     }
     pub fn is_black(&self) -> bool {
-        match self {
-            Piece::Black(_) => true,
-            _ => false,
-        }
+        matches!(self, Piece::Black(_))
     }
     pub fn is_void(&self) -> bool {
-        match self {
-            Piece::Void => true,
-            _ => false,
-        }
+        matches!(self, Piece::Void)
     }
 }
 
@@ -112,3 +181,20 @@ impl Display for Black {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glyphs() {
+        assert_eq!(Glyphs::Ascii.glyph('N'), 'N');
+        assert_eq!(Glyphs::Unicode.glyph('N'), '♘');
+        assert_eq!(Glyphs::Unicode.glyph('n'), '♞');
+        assert_eq!(Glyphs::Filled.glyph('Q'), '♛');
+        assert_eq!(Glyphs::NerdFont.glyph('p'), Glyphs::NerdFont.glyph('P'));
+        assert_eq!(Glyphs::NerdFont.glyph(' '), ' ');
+        assert_eq!(Glyphs::find("Nerd"), Some(Glyphs::NerdFont));
+        assert_eq!(Glyphs::find("emoji"), None);
+    }
+}
@@ -0,0 +1,456 @@
+/*
+ * File: render.rs
+ * Purpose: Drawing the board through pluggable backends
+ * Date: 18.10.2026
+ * */
+
+/* Drawing is split in two. The layout (squares, coordinates, the move list
+ * on the right, FEN under the board) is the same for every backend, only
+ * painting text with colors and showing the finished frame differ:
+ *
+ *  Board  -> Snapshot (what is on the board)  \
+ *                                              -> Renderer::render -> paint() ... show()
+ *  Board  -> UiState (how it should look)     /
+ *
+ * Backends:
+ *  Truecolor       24-bit ANSI colors, most terminals today
+ *  Ansi256         nearest of the 256 xterm colors, for older terminals
+ *  Plain           no colors at all, highlighted squares are in brackets
+ *  StringRenderer  keeps the frames in a string, for tests
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use super::color::{BoardColor, Color};
+use super::piece::{self, Glyphs};
+use super::{bitboard, Board};
+use std::env;
+use std::io::{stdout, Write};
+
+const ESCAPE: &str = "\x1b[0m";
+const PANEL_LINES: usize = 8; // Moves shown on the right of the board
+
+/// Everything on the board that is drawn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub grid: [[char; 8]; 8],                        // Same as Board.board
+    pub last_move: Option<([usize; 2], [usize; 2])>, // Highlighted squares
+    pub check: Option<[usize; 2]>,                   // King in check
+    pub moves: Vec<String>,                          // Lines of the move list
+    pub fen: String,
+}
+
+/// How the board is drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UiState {
+    pub colors: BoardColor,
+    pub white: piece::Color,
+    pub black: piece::Color,
+    pub flipped: bool,
+    pub coordinates: bool,
+    pub glyphs: Glyphs,
+    pub wide: bool,
+}
+
+/// Colors the terminal can show, chooses the backend.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorMode {
+    Truecolor,
+    Ansi256,
+    Plain,
+}
+
+impl ColorMode {
+    // Names used by --colors and the colors command
+    pub const NAMED: [(&'static str, ColorMode); 3] = [
+        ("truecolor", ColorMode::Truecolor),
+        ("256", ColorMode::Ansi256),
+        ("none", ColorMode::Plain),
+    ];
+
+    pub fn find(name: &str) -> Option<ColorMode> {
+        ColorMode::NAMED
+            .iter()
+            .find(|(named, _)| *named == name)
+            .map(|(_, mode)| *mode)
+    }
+
+    /// Guesses the colors of the terminal from the environment: NO_COLOR turns
+    /// them off, COLORTERM tells about truecolor and TERM about 256 colors.
+    pub fn detect() -> ColorMode {
+        let var = |name| env::var(name).unwrap_or_default();
+        if !var("NO_COLOR").is_empty() {
+            ColorMode::Plain
+        } else if matches!(var("COLORTERM").as_str(), "truecolor" | "24bit") {
+            ColorMode::Truecolor
+        } else if var("TERM").ends_with("256color") {
+            ColorMode::Ansi256
+        } else {
+            ColorMode::Truecolor // Most terminals have it even if they don't tell
+        }
+    }
+
+    fn paint(&self, text: &str, background: Color, foreground: Color) -> String {
+        match self {
+            ColorMode::Truecolor => format!(
+                "{}{}{}{}",
+                background.background(),
+                foreground.foreground(),
+                text,
+                ESCAPE
+            ),
+            ColorMode::Ansi256 => format!(
+                "\x1b[48;5;{}m\x1b[38;5;{}m{}{}",
+                xterm_index(background),
+                xterm_index(foreground),
+                text,
+                ESCAPE
+            ),
+            ColorMode::Plain => text.to_owned(),
+        }
+    }
+}
+
+// Nearest of the 6x6x6 color cube and the 24 grays of xterm
+fn xterm_index(color: Color) -> u8 {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let (r, g, b) = color.rgb();
+    let distance = |(r2, g2, b2): (u8, u8, u8)| {
+        [(r, r2), (g, g2), (b, b2)]
+            .iter()
+            .map(|&(a, b)| (a as i32 - b as i32).pow(2))
+            .sum::<i32>()
+    };
+    let level = |channel: u8| {
+        (0..6)
+            .min_by_key(|&i| (LEVELS[i] as i32 - channel as i32).abs())
+            .unwrap()
+    };
+    let (ri, gi, bi) = (level(r), level(g), level(b));
+    let cube = (LEVELS[ri], LEVELS[gi], LEVELS[bi]);
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let gray = ((average.saturating_sub(8) + 5) / 10).min(23) as u8;
+    let gray_value = 8 + gray * 10;
+    match distance((gray_value, gray_value, gray_value)) < distance(cube) {
+        true => 232 + gray,
+        false => (16 + 36 * ri + 6 * gi + bi) as u8,
+    }
+}
+
+pub trait Renderer {
+    /// Text on the background with the foreground color.
+    fn paint(&self, text: &str, background: Color, foreground: Color) -> String;
+
+    /// Shows a finished frame.
+    fn show(&mut self, frame: &str);
+
+    /// False if paint can't show colors, highlights are drawn with brackets then.
+    fn colored(&self) -> bool {
+        true
+    }
+
+    fn render(&mut self, snapshot: &Snapshot, ui: &UiState) {
+        let frame = layout(&*self, snapshot, ui);
+        self.show(&frame);
+    }
+}
+
+/// 24-bit colors written to the output.
+pub struct Truecolor<W: Write>(pub W);
+
+/// 256 colors written to the output.
+pub struct Ansi256<W: Write>(pub W);
+
+/// No colors, written to the output.
+pub struct Plain<W: Write>(pub W);
+
+/// Keeps everything drawn, in the colors of the mode.
+#[derive(Debug, Clone)]
+pub struct StringRenderer {
+    pub mode: ColorMode,
+    pub output: String,
+}
+
+impl StringRenderer {
+    pub fn new(mode: ColorMode) -> Self {
+        StringRenderer {
+            mode,
+            output: String::new(),
+        }
+    }
+}
+
+fn write_frame(output: &mut impl Write, frame: &str) {
+    let _ = output.write_all(frame.as_bytes());
+    let _ = output.flush();
+}
+
+impl<W: Write> Renderer for Truecolor<W> {
+    fn paint(&self, text: &str, background: Color, foreground: Color) -> String {
+        ColorMode::Truecolor.paint(text, background, foreground)
+    }
+
+    fn show(&mut self, frame: &str) {
+        write_frame(&mut self.0, frame);
+    }
+}
+
+impl<W: Write> Renderer for Ansi256<W> {
+    fn paint(&self, text: &str, background: Color, foreground: Color) -> String {
+        ColorMode::Ansi256.paint(text, background, foreground)
+    }
+
+    fn show(&mut self, frame: &str) {
+        write_frame(&mut self.0, frame);
+    }
+}
+
+impl<W: Write> Renderer for Plain<W> {
+    fn paint(&self, text: &str, _: Color, _: Color) -> String {
+        text.to_owned()
+    }
+
+    fn show(&mut self, frame: &str) {
+        write_frame(&mut self.0, frame);
+    }
+
+    fn colored(&self) -> bool {
+        false
+    }
+}
+
+impl Renderer for StringRenderer {
+    fn paint(&self, text: &str, background: Color, foreground: Color) -> String {
+        self.mode.paint(text, background, foreground)
+    }
+
+    fn show(&mut self, frame: &str) {
+        self.output += frame;
+    }
+
+    fn colored(&self) -> bool {
+        self.mode != ColorMode::Plain
+    }
+}
+
+/// Backend drawing to the terminal in the mode.
+pub fn terminal(mode: ColorMode) -> Box<dyn Renderer> {
+    match mode {
+        ColorMode::Truecolor => Box::new(Truecolor(stdout())),
+        ColorMode::Ansi256 => Box::new(Ansi256(stdout())),
+        ColorMode::Plain => Box::new(Plain(stdout())),
+    }
+}
+
+// The whole frame: board with the move list on its right, then the moves and the FEN
+fn layout<R: Renderer + ?Sized>(renderer: &R, snapshot: &Snapshot, ui: &UiState) -> String {
+    // Wide squares are 3 lines and 7 columns, about as high as they are wide
+    let (margin, height) = match ui.wide {
+        true => ("   ", 3),
+        false => (" ", 1),
+    };
+    let middle = height / 2;
+    // Real square [x, y] of what is drawn at rank and file, turning the board
+    // around keeps the colors of the squares
+    let square = |rank: usize, file: usize| match ui.flipped {
+        true => [8 - file, 8 - rank],
+        false => [file + 1, rank + 1],
+    };
+    let (light, dark) = ui.colors.rgb();
+    let glyphs = match renderer.colored() {
+        true => ui.glyphs,
+        false => ui.glyphs.colorless(),
+    };
+
+    let mut frame = String::from("\n");
+    let moves = &snapshot.moves;
+    let mut panel = moves.iter().skip(moves.len().saturating_sub(PANEL_LINES));
+    for rank in 0..8 {
+        for line in 0..height {
+            if ui.coordinates {
+                match line == middle {
+                    true => frame += &format!("{} ", 9 - square(rank, 0)[1]),
+                    false => frame += "  ",
+                }
+            }
+            for file in 0..8 {
+                let at = square(rank, file);
+                let piece = snapshot.grid[at[1] - 1][at[0] - 1];
+                if !" pnbrqkPNBRQK".contains(piece) {
+                    panic!("Invalid FEN");
+                }
+                let (squares, highlighted) = if Some(at) == snapshot.check {
+                    (ui.colors.check(), true)
+                } else if snapshot
+                    .last_move
+                    .is_some_and(|(from, to)| at == from || at == to)
+                {
+                    (ui.colors.last_move(), true)
+                } else {
+                    ((*light, *dark), false)
+                };
+                let background = match (file + rank) % 2 {
+                    0 => squares.0,
+                    _ => squares.1,
+                };
+                let foreground = match piece.is_lowercase() {
+                    true => Color::from(ui.black),
+                    false => Color::from(ui.white),
+                };
+                // The piece is on the middle line of the square
+                let glyph = match line == middle {
+                    true => glyphs.glyph(piece),
+                    false => ' ',
+                };
+                // Without colors highlighted squares are in brackets
+                let text = match highlighted && !renderer.colored() && line == middle {
+                    true => format!("{}[{}]{}", &margin[1..], glyph, &margin[1..]),
+                    false => format!("{}{}{}", margin, glyph, margin),
+                };
+                frame += &renderer.paint(&text, background, foreground);
+            }
+            match line == middle {
+                true => frame += &format!("  {}\n", panel.next().map_or("", |line| line)),
+                false => frame.push('\n'),
+            }
+        }
+    }
+    if ui.coordinates {
+        let files: String = (0..8)
+            .map(|file| (b'a' + square(0, file)[0] as u8 - 1) as char)
+            .map(|name| format!("{margin}{name}{margin}"))
+            .collect();
+        frame += &format!("  {}\n", files);
+    }
+    if !moves.is_empty() {
+        frame += &format!("\nMoves: {}\n", moves.join(" "));
+    }
+    frame += &format!("\n\nFEN: {}\n", snapshot.fen);
+    frame
+}
+
+impl Board {
+    /// What is on the board for a Renderer.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            grid: self.board,
+            last_move: self.history.last().map(|entry| entry.played.decode_move()),
+            check: match self.is_check() {
+                true => self.bitboards.king(self.turn).map(bitboard::square),
+                false => None,
+            },
+            moves: self.move_list(),
+            fen: self.to_fen(),
+        }
+    }
+
+    /// How the board is drawn, from the style of the board.
+    pub fn ui_state(&self) -> UiState {
+        UiState {
+            colors: self.color,
+            white: self.white_color,
+            black: self.black_color,
+            flipped: self.flipped,
+            coordinates: self.coordinates,
+            glyphs: self.glyphs,
+            wide: self.wide,
+        }
+    }
+
+    /// Draws the board with the backend of its color mode.
+    pub fn draw_ascii(&mut self) {
+        self.encode(); // The grid may have been changed by hand
+        terminal(self.colors).render(&self.snapshot(), &self.ui_state());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Move;
+
+    fn render(board: &Board, mode: ColorMode) -> String {
+        let mut renderer = StringRenderer::new(mode);
+        renderer.render(&board.snapshot(), &board.ui_state());
+        renderer.output
+    }
+
+    // Lines without the spaces at the end, which can't be seen
+    fn lines(frame: &str) -> Vec<&str> {
+        frame.lines().map(str::trim_end).collect()
+    }
+
+    #[test]
+    fn test_plain_snapshot() {
+        let mut board = Board::default();
+        board.move_piece(Move::new([5, 7], [5, 5])).unwrap();
+        let expected = "
+8  r  n  b  q  k  b  n  r   1. e4
+7  p  p  p  p  p  p  p  p
+6
+5
+4             [P]
+3
+2  P  P  P  P [ ] P  P  P
+1  R  N  B  Q  K  B  N  R
+   a  b  c  d  e  f  g  h
+
+Moves: 1. e4
+
+
+FEN: rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1
+";
+        assert_eq!(lines(&render(&board, ColorMode::Plain)), lines(expected));
+
+        // A move which isn't played yet is highlighted the same way
+        let mut renderer = StringRenderer::new(ColorMode::Plain);
+        Move::new([7, 1], [6, 3]).draw_gui(&board, &mut renderer);
+        assert!(renderer.output.contains("6                [ ]"));
+        assert!(renderer.output.contains("8  r  n  b  q  k  b [n] r"));
+    }
+
+    #[test]
+    fn test_flipped_wide_and_check() {
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K2R w - - 0 1").unwrap();
+        board.move_piece(Move::new([8, 8], [8, 1])).unwrap(); // Rh8+
+        board.flipped = true;
+        board.coordinates = false;
+        board.glyphs = Glyphs::Unicode;
+        let frame = render(&board, ColorMode::Plain);
+        // Black at the bottom, the king in check and the rook are both highlighted
+        assert_eq!(lines(&frame)[1], "[ ]       ♔               1. Rh8+");
+        assert_eq!(lines(&frame)[8], "[♖]      [♚]");
+
+        board.wide = true;
+        let frame = render(&board, ColorMode::Plain);
+        assert_eq!(lines(&frame)[23], "  [♖]                  [♚]");
+    }
+
+    #[test]
+    fn test_color_backends() {
+        let board = Board::default();
+        let truecolor = render(&board, ColorMode::Truecolor);
+        assert!(truecolor.contains("\x1b[48;2;219;52;0m"));
+        let ansi256 = render(&board, ColorMode::Ansi256);
+        assert!(ansi256.contains("\x1b[48;5;") && !ansi256.contains("48;2;"));
+        assert!(!render(&board, ColorMode::Plain).contains('\x1b'));
+
+        assert_eq!(xterm_index(Color::new((255, 0, 0))), 196);
+        assert_eq!(xterm_index(Color::new((0, 0, 0))), 16);
+        assert_eq!(xterm_index(Color::new((128, 128, 128))), 244);
+        assert_eq!(ColorMode::find("256"), Some(ColorMode::Ansi256));
+    }
+
+    #[test]
+    fn test_glyphs_without_colors() {
+        // Filled and Nerd Font pieces only differ in color
+        let mut board = Board::default();
+        for glyphs in [Glyphs::Filled, Glyphs::NerdFont] {
+            board.glyphs = glyphs;
+            let frame = render(&board, ColorMode::Plain);
+            assert_eq!(lines(&frame)[1], "8  ♜  ♞  ♝  ♛  ♚  ♝  ♞  ♜");
+            assert_eq!(lines(&frame)[8], "1  ♖  ♘  ♗  ♕  ♔  ♗  ♘  ♖");
+        }
+        board.glyphs = Glyphs::Filled;
+        let frame = render(&board, ColorMode::Truecolor);
+        assert!(!frame.contains('♖'));
+    }
+}
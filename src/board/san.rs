@@ -0,0 +1,183 @@
+/*
+ * File: san.rs
+ * Purpose: Writing moves in standard algebraic notation (e4, Nbd7, exd5, O-O, e8=Q#)
+ * Date: 18.10.2026
+ * */
+
+use super::chess_move::{square_name, Move};
+use super::Board;
+
+impl Board {
+    /// SAN of a legal move in this position. A promotion without a piece
+    /// is written as a queen promotion, that's what make_move plays.
+    pub fn to_san(&self, current_move: &Move) -> String {
+        let (from, to) = current_move.decode_move();
+        let piece = self.board[from[1] - 1][from[0] - 1];
+        let kind = piece.to_ascii_uppercase();
+        let capture =
+            self.board[to[1] - 1][to[0] - 1] != ' ' || (kind == 'P' && Some(to) == self.en_passant);
+
+        let mut san = String::with_capacity(8);
+        if let Some(king_side) = self.castling_side(current_move) {
+            san.push_str(if king_side { "O-O" } else { "O-O-O" });
+        } else if kind == 'P' {
+            if capture {
+                san.push(square_name(from).chars().next().unwrap());
+                san.push('x');
+            }
+            san.push_str(&square_name(to));
+        } else {
+            san.push(kind);
+            san.push_str(&self.disambiguation(current_move));
+            if capture {
+                san.push('x');
+            }
+            san.push_str(&square_name(to));
+        }
+
+        // Playing the move to see if it gives check
+        let mut next = self.clone();
+        if next.make_move(current_move) == super::MoveType::Promotion {
+            let piece = current_move.promotion().unwrap_or('q');
+            san.push('=');
+            san.push(piece.to_ascii_uppercase());
+        }
+        if next.is_checkmate() {
+            san.push('#');
+        } else if next.is_check() {
+            san.push('+');
+        }
+        san
+    }
+
+    /* When two pieces of the same kind can go to the same square, the file
+     * of the moving piece is added, if that's not enough the rank, and if
+     * even that's not enough both of them. */
+    fn disambiguation(&self, current_move: &Move) -> String {
+        let (from, to) = current_move.decode_move();
+        let piece = self.board[from[1] - 1][from[0] - 1];
+        let rivals: Vec<[usize; 2]> = self
+            .legal_moves()
+            .iter()
+            .map(|m| m.decode_move())
+            .filter(|(other, target)| {
+                *target == to && *other != from && self.board[other[1] - 1][other[0] - 1] == piece
+            })
+            .map(|(other, _)| other)
+            .collect();
+
+        let name = square_name(from);
+        if rivals.is_empty() {
+            String::new()
+        } else if rivals.iter().all(|other| other[0] != from[0]) {
+            name[..1].to_owned()
+        } else if rivals.iter().all(|other| other[1] != from[1]) {
+            name[1..].to_owned()
+        } else {
+            name
+        }
+    }
+
+    /// SAN of every played move, from the first one to the last.
+    pub fn san_history(&self) -> Vec<String> {
+        // Going back to the start and playing everything again
+        let mut replay = self.clone();
+        let mut entries = Vec::with_capacity(self.history.len());
+        while let Some(entry) = replay.unmake_move() {
+            entries.push(entry);
+        }
+
+        let mut sans = Vec::with_capacity(entries.len());
+        for entry in entries.into_iter().rev() {
+            sans.push(replay.to_san(&entry.played));
+            replay.make_move(&entry.played);
+        }
+        sans
+    }
+
+    /// Played moves numbered like "1. e4 e5 2. Nf3", one line per move number.
+    pub fn move_list(&self) -> Vec<String> {
+        let sans = self.san_history();
+        let first = match self.history.first() {
+            Some(entry) => entry,
+            None => return Vec::new(),
+        };
+        let black_first = first.moved.is_ascii_lowercase();
+
+        let mut lines: Vec<String> = Vec::new();
+        let mut number = first.fullmove_number;
+        let mut sans = sans.iter();
+        if black_first {
+            lines.push(format!("{}... {}", number, sans.next().unwrap()));
+            number += 1;
+        }
+        while let Some(white) = sans.next() {
+            match sans.next() {
+                Some(black) => lines.push(format!("{}. {} {}", number, white, black)),
+                None => lines.push(format!("{}. {}", number, white)),
+            }
+            number += 1;
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn san(fen: &str, from: [usize; 2], to: [usize; 2]) -> String {
+        Board::from_fen(fen).unwrap().to_san(&Move::new(from, to))
+    }
+
+    #[test]
+    fn test_simple_san() {
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(san(start, [5, 7], [5, 5]), "e4");
+        assert_eq!(san(start, [7, 8], [6, 6]), "Nf3");
+
+        let fen = "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2";
+        assert_eq!(san(fen, [5, 5], [4, 4]), "exd5");
+        assert_eq!(san(fen, [6, 8], [2, 4]), "Bb5+");
+    }
+
+    #[test]
+    fn test_disambiguation() {
+        let fen = "4k3/8/8/8/8/8/8/1N1K1N2 w - - 0 1";
+        assert_eq!(san(fen, [2, 8], [4, 7]), "Nbd2");
+        let fen = "4k3/8/8/8/R7/8/8/R3K3 w - - 0 1";
+        assert_eq!(san(fen, [1, 8], [1, 7]), "R1a2");
+        let fen = "4k3/8/8/8/8/8/Q1Q5/Q3K3 w - - 0 1";
+        assert_eq!(san(fen, [1, 7], [2, 8]), "Qa2b1");
+    }
+
+    #[test]
+    fn test_special_san() {
+        let fen = "r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1";
+        assert_eq!(san(fen, [5, 8], [7, 8]), "O-O");
+        assert_eq!(san(fen, [5, 8], [3, 8]), "O-O-O");
+        assert_eq!(san(fen, [2, 2], [1, 1]), "bxa8=Q+");
+        let board = Board::from_fen(fen).unwrap();
+        let promotion = Move::new([2, 2], [2, 1]).with_promotion('n');
+        assert_eq!(board.to_san(&promotion), "b8=N");
+
+        let fen = "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1";
+        assert_eq!(san(fen, [1, 8], [1, 1]), "Ra8#");
+    }
+
+    #[test]
+    fn test_move_list() {
+        let mut board = Board::default();
+        assert!(board.move_list().is_empty());
+        for (from, to) in [([5, 7], [5, 5]), ([5, 2], [5, 4]), ([7, 8], [6, 6])] {
+            board.move_piece(Move::new(from, to)).unwrap();
+        }
+        assert_eq!(board.san_history(), vec!["e4", "e5", "Nf3"]);
+        assert_eq!(board.move_list(), vec!["1. e4 e5", "2. Nf3"]);
+
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 b - - 0 30").unwrap();
+        board.move_piece(Move::new([5, 1], [4, 1])).unwrap();
+        board.move_piece(Move::new([5, 8], [4, 8])).unwrap();
+        assert_eq!(board.move_list(), vec!["30... Kd8", "31. Kd1"]);
+    }
+}
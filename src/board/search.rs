@@ -0,0 +1,512 @@
+/*
+ * File: search.rs
+ * Purpose: Finding the best move with an alpha-beta search
+ * Date: 18.10.2026
+ * */
+
+/* The search is a negamax with alpha-beta pruning: every position is
+ * scored from the side to move's point of view, so the score of a move is
+ * the negated score of the opponent's best reply.
+ *
+ * It is run with iterative deepening (depth 1, 2, 3 ...) which lets it stop
+ * when the time runs out and still have the best move of the last finished
+ * depth. Good moves are searched first so more of the tree can be cut:
+ *
+ *  1. best move from the transposition table
+ *  2. move of the principal variation from the previous depth
+ *  3. captures, most valuable victim by least valuable attacker (MVV-LVA)
+ *  4. queen promotions
+ *  5. killer moves (quiet moves which caused a cut at the same ply)
+ *  6. other quiet moves, ordered by how often they caused a cut (history)
+ *  7. underpromotions
+ *
+ * At depth 0 a quiescence search plays captures until the position is quiet,
+ * so the evaluation is never done in the middle of an exchange.
+ *
+ * Positions found in the transposition table at enough depth return their
+ * stored score right away, repeated positions are scored as draws.
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use super::chess_move::Move;
+use super::transposition::{Bound, TranspositionTable};
+use super::Board;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub const MATE: i32 = 100_000; // Score of being mated right now
+pub const MAX_DEPTH: u32 = MAX_PLY as u32 - 1;
+const INFINITY: i32 = 1_000_000;
+const MAX_PLY: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchLimits {
+    pub depth: u32,             // Deepest iteration to search
+    pub time: Option<Duration>, // Search is stopped after this much time
+}
+
+impl Default for SearchLimits {
+    fn default() -> Self {
+        SearchLimits {
+            depth: 5,
+            time: Some(Duration::from_secs(5)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub best_move: Option<Move>, // None if there are no legal moves
+    pub score: i32,              // Centipawns for the side to move
+    pub depth: u32,              // Last finished depth
+    pub nodes: u64,
+    pub time: Duration, // Time spent until the last finished depth
+    pub pv: Vec<Move>,  // Expected line of play, starting with best_move
+}
+
+impl SearchResult {
+    /// Moves until mate if the score is a mate, negative if the side to move gets mated.
+    pub fn mate_in(&self) -> Option<i32> {
+        let plies = MATE - self.score.abs();
+        if plies > MAX_PLY as i32 {
+            return None;
+        }
+        let moves = (plies + 1) / 2;
+        Some(if self.score > 0 { moves } else { -moves })
+    }
+
+    /// Nodes searched per second.
+    pub fn nps(&self) -> u64 {
+        let micros = self.time.as_micros().max(1) as u64;
+        self.nodes.saturating_mul(1_000_000) / micros
+    }
+}
+
+// Values used to order captures, the king only shows up as an attacker
+fn piece_value(piece: char) -> i32 {
+    match piece.to_ascii_lowercase() {
+        'p' => 100,
+        'n' => 320,
+        'b' => 330,
+        'r' => 500,
+        'q' => 900,
+        'k' => 2000,
+        _ => 0,
+    }
+}
+
+fn index(square: [usize; 2]) -> usize {
+    (square[1] - 1) * 8 + square[0] - 1
+}
+
+struct Searcher<'a> {
+    board: Board,
+    table: &'a mut TranspositionTable,
+    limits: SearchLimits,
+    start: Instant,
+    stopped: bool,
+    stop: Arc<AtomicBool>, // Set from outside to stop the search
+    nodes: u64,
+    killers: [[Option<Move>; 2]; MAX_PLY],
+    history: Vec<[i32; 64]>, // Indexed by from and to squares
+    pv: Vec<Vec<Move>>,      // Best line found from every ply
+    last_pv: Vec<Move>,      // Principal variation of the previous depth
+}
+
+impl<'a> Searcher<'a> {
+    fn new(
+        board: &Board,
+        limits: SearchLimits,
+        stop: Arc<AtomicBool>,
+        table: &'a mut TranspositionTable,
+    ) -> Searcher<'a> {
+        table.new_search();
+        Searcher {
+            board: board.clone(),
+            table,
+            limits,
+            start: Instant::now(),
+            stopped: false,
+            stop,
+            nodes: 0,
+            killers: std::array::from_fn(|_| [None, None]),
+            history: vec![[0; 64]; 64],
+            pv: vec![Vec::new(); MAX_PLY + 1],
+            last_pv: Vec::new(),
+        }
+    }
+
+    fn out_of_time(&mut self) -> bool {
+        // Looking at the clock every 1024 nodes is enough
+        if !self.stopped && self.nodes.is_multiple_of(1024) {
+            self.stopped = self.stop.load(Ordering::Relaxed)
+                || self
+                    .limits
+                    .time
+                    .is_some_and(|time| self.start.elapsed() >= time);
+        }
+        self.stopped
+    }
+
+    fn is_capture(&self, current_move: &Move) -> bool {
+        let to = current_move.decode_move().1;
+        // In Chess960 the king castles by taking its own rook
+        let taken = self.board.board[to[1] - 1][to[0] - 1] != ' '
+            && self.board.castling_side(current_move).is_none();
+        taken || self.is_en_passant(current_move)
+    }
+
+    fn is_en_passant(&self, current_move: &Move) -> bool {
+        let (from, to) = current_move.decode_move();
+        Some(to) == self.board.en_passant
+            && self.board.board[from[1] - 1][from[0] - 1].eq_ignore_ascii_case(&'p')
+    }
+
+    fn order(&self, moves: &mut [Move], ply: usize, table_move: Option<&Move>) {
+        let board = &self.board.board;
+        let pv_move = self.last_pv.get(ply);
+        moves.sort_by_cached_key(|m| {
+            let (from, to) = m.decode_move();
+            let score = if Some(m) == table_move {
+                2_000_000
+            } else if Some(m) == pv_move {
+                1_000_000
+            } else if matches!(m.promotion(), Some(piece) if piece != 'q') {
+                -100_000 // A queen is almost always better
+            } else if self.is_capture(m) {
+                let victim = match self.is_en_passant(m) {
+                    true => 'p',
+                    false => board[to[1] - 1][to[0] - 1],
+                };
+                let attacker = board[from[1] - 1][from[0] - 1];
+                100_000 + piece_value(victim) * 10 - piece_value(attacker)
+            } else if m.promotion().is_some() {
+                90_000
+            } else if self.killers[ply][0].as_ref() == Some(m) {
+                80_000
+            } else if self.killers[ply][1].as_ref() == Some(m) {
+                79_000
+            } else {
+                self.history[index(from)][index(to)].min(70_000)
+            };
+            -score
+        });
+    }
+
+    fn play(&mut self, current_move: &Move) {
+        self.nodes += 1;
+        self.board.make_move(current_move);
+    }
+
+    fn quiescence(&mut self, mut alpha: i32, beta: i32, ply: usize) -> i32 {
+        self.pv[ply].clear();
+        if self.out_of_time() {
+            return 0;
+        }
+        let stand_pat = self.board.static_evaluate();
+        if stand_pat >= beta || ply >= MAX_PLY {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+
+        let mut moves: Vec<Move> = self
+            .board
+            .legal_moves()
+            .into_iter()
+            .filter(|m| match m.promotion() {
+                Some(piece) => piece == 'q', // Underpromotions are left to the main search
+                None => self.is_capture(m),
+            })
+            .collect();
+        self.order(&mut moves, ply, None);
+        for current_move in moves {
+            self.play(&current_move);
+            let score = -self.quiescence(-beta, -alpha, ply + 1);
+            self.board.unmake_move();
+            if self.stopped {
+                return 0;
+            }
+            if score >= beta {
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+
+    fn negamax(&mut self, depth: u32, mut alpha: i32, beta: i32, ply: usize) -> i32 {
+        self.pv[ply].clear();
+        if self.out_of_time() {
+            return 0;
+        }
+        if ply > 0
+            && (self.board.halfmove_clock >= 100
+                || self.board.repetitions() > 0
+                || self.board.is_insufficient_material())
+        {
+            return 0;
+        }
+        let key = self.board.hash;
+        let table_move = match self.table.probe(key) {
+            Some(entry) => {
+                let score = entry.score(ply);
+                let usable = match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => score >= beta,
+                    Bound::Upper => score <= alpha,
+                };
+                // The root always searches, so it has a line to report
+                if ply > 0 && entry.depth >= depth && usable {
+                    return score;
+                }
+                entry.best_move.clone()
+            }
+            None => None,
+        };
+        let in_check = self.board.is_check();
+        // Looking one move deeper when in check, so checks don't hide threats
+        let depth = if in_check && ply > 0 {
+            depth + 1
+        } else {
+            depth
+        };
+        if depth == 0 || ply >= MAX_PLY - 1 {
+            return self.quiescence(alpha, beta, ply);
+        }
+
+        let mut moves = self.board.legal_moves();
+        if moves.is_empty() {
+            // Faster mates score higher
+            return if in_check { -MATE + ply as i32 } else { 0 };
+        }
+        self.order(&mut moves, ply, table_move.as_ref());
+
+        let original_alpha = alpha;
+        let mut best = -INFINITY;
+        let mut best_move = None;
+        for current_move in moves {
+            let quiet = !self.is_capture(&current_move) && current_move.promotion().is_none();
+            self.play(&current_move);
+            let score = -self.negamax(depth - 1, -beta, -alpha, ply + 1);
+            self.board.unmake_move();
+            if self.stopped {
+                return 0;
+            }
+
+            if score > best {
+                best = score;
+                best_move = Some(current_move.clone());
+            }
+            if score > alpha {
+                alpha = score;
+                let mut line = vec![current_move.clone()];
+                line.append(&mut self.pv[ply + 1]);
+                self.pv[ply] = line;
+            }
+            if alpha >= beta {
+                if quiet {
+                    let (from, to) = current_move.decode_move();
+                    self.history[index(from)][index(to)] += (depth * depth) as i32;
+                    if self.killers[ply][0].as_ref() != Some(&current_move) {
+                        self.killers[ply][1] = self.killers[ply][0].take();
+                        self.killers[ply][0] = Some(current_move);
+                    }
+                }
+                break;
+            }
+        }
+
+        let bound = if best >= beta {
+            Bound::Lower
+        } else if best > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        self.table.store(key, depth, best, ply, bound, best_move);
+        best
+    }
+
+    fn run(&mut self, report: &mut impl FnMut(&SearchResult)) -> SearchResult {
+        let mut result = SearchResult {
+            best_move: self.board.legal_moves().first().cloned(),
+            score: 0,
+            depth: 0,
+            nodes: 0,
+            time: Duration::ZERO,
+            pv: Vec::new(),
+        };
+        for depth in 1..=self.limits.depth.max(1) {
+            let score = self.negamax(depth, -INFINITY, INFINITY, 0);
+            // An unfinished depth is thrown away, the first legal move stays if nothing finished
+            if self.stopped {
+                break;
+            }
+            self.last_pv = self.pv[0].clone();
+            result.score = score;
+            result.depth = depth;
+            result.best_move = self.last_pv.first().cloned().or(result.best_move);
+            result.pv = self.last_pv.clone();
+            result.nodes = self.nodes;
+            result.time = self.start.elapsed();
+            report(&result);
+            if score.abs() >= MATE - MAX_PLY as i32 {
+                break; // Mate was found, no need to look deeper
+            }
+        }
+        result
+    }
+}
+
+impl Board {
+    /// Searches for the best move of the side to move within the limits.
+    pub fn search(&self, limits: SearchLimits) -> SearchResult {
+        let mut table = TranspositionTable::default();
+        self.search_with(limits, Arc::new(AtomicBool::new(false)), &mut table, |_| ())
+    }
+
+    /// Same as search, but can be stopped from another thread with the flag
+    /// and calls report with the result of every finished depth. The table
+    /// can be kept between searches so later moves use what was found before.
+    pub fn search_with(
+        &self,
+        limits: SearchLimits,
+        stop: Arc<AtomicBool>,
+        table: &mut TranspositionTable,
+        mut report: impl FnMut(&SearchResult),
+    ) -> SearchResult {
+        Searcher::new(self, limits, stop, table).run(&mut report)
+    }
+
+    /* Score of the position without looking ahead, from the side to
+     * move's point of view in centipawns */
+    pub(crate) fn static_evaluate(&self) -> i32 {
+        let score = self.evaluation().total();
+        if self.turn {
+            score
+        } else {
+            -score
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn best(fen: &str, depth: u32) -> SearchResult {
+        let limits = SearchLimits { depth, time: None };
+        Board::from_fen(fen).unwrap().search(limits)
+    }
+
+    #[test]
+    fn test_takes_free_piece() {
+        let result = best("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1", 2);
+        assert_eq!(result.best_move, Some(Move::new([4, 8], [4, 4])));
+        assert!(result.score > 400);
+    }
+
+    #[test]
+    fn test_mate_in_one() {
+        let result = best("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 3);
+        assert_eq!(result.best_move, Some(Move::new([1, 8], [1, 1])));
+        assert_eq!(result.score, MATE - 1);
+    }
+
+    #[test]
+    fn test_mate_in_two() {
+        // Back rank mate: 1. Re8+ Rxe8 2. Rxe8#
+        let result = best("r5k1/5ppp/8/8/8/8/4RPPP/4R1K1 w - - 0 1", 4);
+        assert_eq!(result.score, MATE - 3);
+        assert_eq!(result.pv.len(), 3);
+    }
+
+    #[test]
+    fn test_avoids_losing_the_queen() {
+        // Qxd5 is defended by the e6 pawn
+        let result = best("4k3/8/4p3/3p4/8/8/3Q4/4K3 w - - 0 1", 3);
+        assert_ne!(result.best_move, Some(Move::new([4, 7], [4, 4])));
+    }
+
+    #[test]
+    fn test_mate_score() {
+        let result = best("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 3);
+        assert_eq!(result.mate_in(), Some(1));
+        let result = best("6k1/5ppp/8/8/8/8/8/R5K1 b - - 0 1", 3);
+        assert_eq!(result.mate_in(), None);
+        // Every move of black runs into the same mate
+        let result = best("7k/8/6K1/8/8/8/8/R7 b - - 0 1", 3);
+        assert_eq!(result.mate_in(), Some(-1));
+    }
+
+    #[test]
+    fn test_stop_and_report() {
+        let stop = Arc::new(AtomicBool::new(true));
+        let limits = SearchLimits {
+            depth: MAX_DEPTH,
+            time: None,
+        };
+        let mut depths = Vec::new();
+        let mut table = TranspositionTable::new(1);
+        let result =
+            Board::default().search_with(limits, stop, &mut table, |info| depths.push(info.depth));
+        // Nothing finished, so nothing is reported but there is still a move to play
+        assert!(depths.is_empty());
+        assert!(result.best_move.is_some());
+        assert_eq!((result.depth, result.score), (0, 0));
+        assert!(result.pv.is_empty());
+    }
+
+    #[test]
+    fn test_no_moves() {
+        let result = best("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", 3);
+        assert_eq!(result.best_move, None);
+        assert_eq!(result.score, 0);
+    }
+
+    #[test]
+    fn test_time_limit() {
+        let limits = SearchLimits {
+            depth: 30,
+            time: Some(Duration::from_millis(200)),
+        };
+        let start = Instant::now();
+        let result = Board::default().search(limits);
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert!(result.best_move.is_some());
+        assert!(result.depth >= 1);
+    }
+
+    #[test]
+    fn test_table_is_kept_between_searches() {
+        let limits = SearchLimits {
+            depth: 4,
+            time: None,
+        };
+        let board = Board::default();
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut table = TranspositionTable::new(1);
+        let first = board.search_with(limits, stop.clone(), &mut table, |_| ());
+        let second = board.search_with(limits, stop, &mut table, |_| ());
+        assert!(second.nodes < first.nodes);
+        assert_eq!(second.score, first.score);
+    }
+
+    #[test]
+    fn test_chess960_castling_is_quiet() {
+        let mut board = Board::default();
+        board
+            .set_fen_chess960("1r4kr/8/8/8/8/8/8/1R4KR w HBhb - 0 1", true)
+            .unwrap();
+        let mut table = TranspositionTable::new(1);
+        let limits = SearchLimits {
+            depth: 1,
+            time: None,
+        };
+        let searcher = Searcher::new(&board, limits, Arc::new(AtomicBool::new(false)), &mut table);
+        let castling = Move::new([7, 8], [8, 8]);
+        assert!(board.legal_moves().contains(&castling));
+        assert!(!searcher.is_capture(&castling));
+        assert!(searcher.is_capture(&Move::new([2, 8], [2, 1])));
+    }
+}
@@ -0,0 +1,196 @@
+/*
+ * File: transposition.rs
+ * Purpose: Remembering searched positions so the search doesn't do the same work twice
+ * Date: 18.10.2026
+ * */
+
+/* The same position is reached through many move orders (transpositions).
+ * The table is a fixed size array indexed by the Zobrist key of the
+ * position. Every slot keeps one position with what the search found:
+ *
+ *  key | depth | score | bound | best move | generation
+ *
+ * The score is only exact if it was between alpha and beta, otherwise it is
+ * a lower bound (the move was good enough for a cut) or an upper bound
+ * (nothing reached alpha). The best move is worth searching first even when
+ * the score can't be used.
+ *
+ * When two positions want the same slot the new one replaces the old one if
+ * the old one is left from an earlier search or was searched less deep.
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use super::chess_move::Move;
+use super::search::{MATE, MAX_DEPTH};
+use std::mem::size_of;
+
+pub const DEFAULT_HASH_MB: usize = 16;
+pub const MAX_HASH_MB: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bound {
+    Exact,
+    Lower, // Real score is this or more
+    Upper, // Real score is this or less
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub key: u64,
+    pub depth: u32,
+    score: i32, // Mates are counted from this position, not from the root
+    pub bound: Bound,
+    pub best_move: Option<Move>,
+    generation: u8,
+}
+
+// Mates are stored as distance from this position so they stay right at any ply
+fn mate_distance(score: i32) -> bool {
+    score.abs() >= MATE - MAX_DEPTH as i32 - 1
+}
+
+impl Entry {
+    /// Score as seen from the root of a search at the given ply.
+    pub fn score(&self, ply: usize) -> i32 {
+        match mate_distance(self.score) {
+            true => self.score - self.score.signum() * ply as i32,
+            false => self.score,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TranspositionTable {
+    slots: Vec<Option<Entry>>,
+    generation: u8, // Increased by every search, older entries are replaced first
+}
+
+impl TranspositionTable {
+    /// Table taking about `megabytes` of memory, at least 1 MB.
+    pub fn new(megabytes: usize) -> Self {
+        let bytes = megabytes.clamp(1, MAX_HASH_MB) * 1024 * 1024;
+        TranspositionTable {
+            slots: vec![None; bytes / size_of::<Option<Entry>>()],
+            generation: 0,
+        }
+    }
+
+    /// Size of the table in MB, rounded down.
+    #[allow(dead_code)]
+    pub fn megabytes(&self) -> usize {
+        self.slots.len() * size_of::<Option<Entry>>() / (1024 * 1024)
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+        self.generation = 0;
+    }
+
+    /// Called when a search starts, entries of the earlier searches get old.
+    pub fn new_search(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    fn slot(&self, key: u64) -> usize {
+        (key % self.slots.len() as u64) as usize
+    }
+
+    pub fn probe(&self, key: u64) -> Option<&Entry> {
+        self.slots[self.slot(key)]
+            .as_ref()
+            .filter(|entry| entry.key == key)
+    }
+
+    pub fn store(
+        &mut self,
+        key: u64,
+        depth: u32,
+        score: i32,
+        ply: usize,
+        bound: Bound,
+        best_move: Option<Move>,
+    ) {
+        let generation = self.generation;
+        let slot = self.slot(key);
+        let replace = match &self.slots[slot] {
+            None => true,
+            Some(old) => old.key == key || old.generation != generation || depth >= old.depth,
+        };
+        if !replace {
+            return;
+        }
+        // A position searched again without finding a move keeps its old best move
+        let best_move = match (&self.slots[slot], best_move) {
+            (Some(old), None) if old.key == key => old.best_move.clone(),
+            (_, best_move) => best_move,
+        };
+        let score = match mate_distance(score) {
+            true => score + score.signum() * ply as i32,
+            false => score,
+        };
+        self.slots[slot] = Some(Entry {
+            key,
+            depth,
+            score,
+            bound,
+            best_move,
+            generation,
+        });
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        TranspositionTable::new(DEFAULT_HASH_MB)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_and_probe() {
+        let mut table = TranspositionTable::new(1);
+        assert_eq!(table.megabytes(), 1);
+        let best = Move::new([5, 7], [5, 5]);
+        table.store(42, 3, 25, 0, Bound::Exact, Some(best.clone()));
+        let entry = table.probe(42).unwrap();
+        assert_eq!(
+            (entry.depth, entry.score(0), entry.bound),
+            (3, 25, Bound::Exact)
+        );
+        assert_eq!(entry.best_move, Some(best));
+        assert!(table.probe(43).is_none());
+
+        table.clear();
+        assert!(table.probe(42).is_none());
+    }
+
+    #[test]
+    fn test_replacement() {
+        let mut table = TranspositionTable::new(1);
+        let size = table.slots.len() as u64;
+        // Both keys want the same slot
+        table.store(7, 5, 0, 0, Bound::Lower, None);
+        table.store(7 + size, 2, 0, 0, Bound::Lower, None);
+        assert!(table.probe(7).is_some(), "a deeper entry stays");
+
+        table.new_search();
+        table.store(7 + size, 2, 0, 0, Bound::Lower, None);
+        assert!(
+            table.probe(7).is_none(),
+            "entries of older searches are replaced"
+        );
+        assert!(table.probe(7 + size).is_some());
+    }
+
+    #[test]
+    fn test_mate_scores_follow_the_ply() {
+        let mut table = TranspositionTable::new(1);
+        // Mate in 3 plies seen from ply 2 is a mate in 1 ply from the stored position
+        table.store(1, 4, MATE - 3, 2, Bound::Exact, None);
+        assert_eq!(table.probe(1).unwrap().score(2), MATE - 3);
+        assert_eq!(table.probe(1).unwrap().score(4), MATE - 5);
+        assert_eq!(table.probe(1).unwrap().score(0), MATE - 1);
+    }
+}
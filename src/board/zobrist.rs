@@ -0,0 +1,187 @@
+/*
+ * File: zobrist.rs
+ * Purpose: Giving every position a 64-bit key for the transposition table and repetitions
+ * Date: 18.10.2026
+ * */
+
+/* A Zobrist key is the xor of a random number for every piece on its
+ * square, every castling right, the en passant file and the side to move.
+ * Xor undoes itself, so a move only has to xor out what changed and xor in
+ * the new things instead of looking at the whole board again:
+ *
+ *  hash ^= PIECES[knight on g1] ^ PIECES[knight on f3] ^ SIDE
+ *
+ * The en passant file only counts when a pawn can really take en passant,
+ * otherwise the same position would get two keys after a double push.
+ * Random numbers come from a fixed seed so keys are the same every run.
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use super::bitboard::{self, side, PAWN, PAWN_ATTACKS};
+use super::castling::CastlingRights;
+use super::Board;
+
+const KEYS: [u64; 12 * 64 + 4 + 8 + 1] = random_keys();
+const CASTLING: usize = 12 * 64; // Keys of KQkq start here
+const EN_PASSANT: usize = CASTLING + 4; // Keys of the files a to h
+pub const SIDE: u64 = KEYS[EN_PASSANT + 8]; // Black to move
+
+// Xorshift at compile time
+const fn random_keys<const N: usize>() -> [u64; N] {
+    let mut keys = [0; N];
+    let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+    let mut i = 0;
+    while i < N {
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        keys[i] = state.wrapping_mul(2685821657736338717);
+        i += 1;
+    }
+    keys
+}
+
+/// Key of the piece on the bit of bitboard.rs, 0 for an empty square.
+pub fn piece_key(piece: char, index: usize) -> u64 {
+    match bitboard::kind(piece) {
+        Some(kind) => {
+            let color = side(piece.is_ascii_uppercase());
+            KEYS[(color * 6 + kind) * 64 + index]
+        }
+        None => 0,
+    }
+}
+
+pub fn castling_key(rights: &CastlingRights) -> u64 {
+    [
+        rights.white_king,
+        rights.white_queen,
+        rights.black_king,
+        rights.black_queen,
+    ]
+    .iter()
+    .enumerate()
+    .filter(|(_, &allowed)| allowed)
+    .fold(0, |key, (i, _)| key ^ KEYS[CASTLING + i])
+}
+
+impl Board {
+    /// Key of the position made from scratch, make_move keeps `hash` up to date without it.
+    pub fn compute_hash(&self) -> u64 {
+        let mut hash = castling_key(&self.castling) ^ self.en_passant_key();
+        for (index, &piece) in self.board.iter().flatten().enumerate() {
+            hash ^= piece_key(piece, index);
+        }
+        match self.turn {
+            true => hash,
+            false => hash ^ SIDE,
+        }
+    }
+
+    // Key of the en passant file if a pawn of the side to move can take there
+    pub(super) fn en_passant_key(&self) -> u64 {
+        let target = match self.en_passant {
+            Some(target) => bitboard::index(target),
+            None => return 0,
+        };
+        // Pawns which could take are where an enemy pawn on the target would attack
+        let pawns = self.bitboards.pieces[side(self.turn)][PAWN];
+        match PAWN_ATTACKS[side(!self.turn)][target] & pawns {
+            0 => 0,
+            _ => KEYS[EN_PASSANT + target % 8],
+        }
+    }
+
+    /// How many times the position was on the board before. Only positions
+    /// since the last capture or pawn move can be the same.
+    pub fn repetitions(&self) -> usize {
+        self.history
+            .iter()
+            .rev()
+            .take(self.halfmove_clock as usize)
+            .skip(1) // The side to move has to be the same
+            .step_by(2)
+            .filter(|entry| entry.hash == self.hash)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Move;
+
+    fn play(board: &mut Board, moves: &[([usize; 2], [usize; 2])]) {
+        for (from, to) in moves {
+            board.move_piece(Move::new(*from, *to)).unwrap();
+            assert_eq!(board.hash, board.compute_hash());
+        }
+    }
+
+    #[test]
+    fn test_incremental_hash() {
+        let fen = "r3k2r/8/8/8/4p3/8/3P4/R3K2R w KQkq - 3 10";
+        let mut board = Board::from_fen(fen).unwrap();
+        let start = board.hash;
+        play(
+            &mut board,
+            &[
+                ([4, 7], [4, 5]), // d4
+                ([5, 5], [4, 6]), // exd3 en passant
+                ([5, 8], [3, 8]), // O-O-O
+                ([8, 1], [8, 8]), // Rxh1+
+            ],
+        );
+        while board.undo_move().is_some() {
+            assert_eq!(board.hash, board.compute_hash());
+        }
+        assert_eq!(board.hash, start);
+    }
+
+    #[test]
+    fn test_transpositions_have_the_same_key() {
+        let mut first = Board::default();
+        let mut second = Board::default();
+        play(
+            &mut first,
+            &[([7, 8], [6, 6]), ([7, 1], [6, 3]), ([2, 8], [3, 6])],
+        );
+        play(
+            &mut second,
+            &[([2, 8], [3, 6]), ([7, 1], [6, 3]), ([7, 8], [6, 6])],
+        );
+        assert_eq!(first.hash, second.hash);
+        assert_ne!(first.hash, Board::default().hash);
+    }
+
+    #[test]
+    fn test_en_passant_only_counts_when_possible() {
+        // No black pawn can take on e3, so the key is the same as without it
+        let with = Board::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1").unwrap();
+        let without = Board::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - - 0 1").unwrap();
+        assert_eq!(with.hash, without.hash);
+
+        let with = Board::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").unwrap();
+        let without = Board::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1").unwrap();
+        assert_ne!(with.hash, without.hash);
+    }
+
+    #[test]
+    fn test_repetitions() {
+        let mut board = Board::default();
+        let knights = [
+            ([7, 8], [6, 6]), // Nf3
+            ([7, 1], [6, 3]), // Nf6
+            ([6, 6], [7, 8]), // Ng1
+            ([6, 3], [7, 1]), // Ng8
+        ];
+        assert_eq!(board.repetitions(), 0);
+        play(&mut board, &knights);
+        assert_eq!(board.repetitions(), 1);
+        play(&mut board, &knights);
+        assert_eq!(board.repetitions(), 2);
+
+        // A pawn move makes the earlier positions unreachable
+        play(&mut board, &[([5, 7], [5, 5])]);
+        assert_eq!(board.repetitions(), 0);
+    }
+}
@@ -0,0 +1,162 @@
+/*
+ * File: clock.rs
+ * Purpose: Chess clocks for games played with a time control
+ * Date: 18.10.2026
+ * */
+
+/* A time control is written like on chess sites, minutes for the whole
+ * game and seconds added after every move:
+ *
+ *  5+3     5 minutes, 3 seconds increment
+ *  10      10 minutes, no increment
+ *  0.5+1   30 seconds, 1 second increment
+ *
+ * Only the clock of the side to move runs. Ferris gives every move a part
+ * of what is left on its clock, the same way as in xboard.rs.
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use std::fmt::{Display, Formatter};
+use std::time::{Duration, Instant};
+
+const MOVES_TO_GO: u32 = 30; // Guess of the moves left in the game
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeControl {
+    pub base: Duration,
+    pub increment: Duration,
+}
+
+impl TimeControl {
+    /// Reads "minutes+increment seconds", the increment can be left out.
+    pub fn parse(text: &str) -> Result<TimeControl, String> {
+        let (base, increment) = text.split_once('+').unwrap_or((text, "0"));
+        // Negative, infinite and too big times can't be a Duration
+        let seconds = |number: &str, scale: f64| {
+            number
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .and_then(|number| Duration::try_from_secs_f64(number * scale).ok())
+                .ok_or(format!("Invalid time control {}, write it like 5+3", text))
+        };
+        let base = seconds(base, 60.0)?;
+        if base.is_zero() {
+            return Err("There has to be some time on the clock".to_owned());
+        }
+        Ok(TimeControl {
+            base,
+            increment: seconds(increment, 1.0)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Clock {
+    left: [Duration; 2], // [white, black]
+    increment: Duration,
+    started: Option<Instant>, // When the side to move started thinking
+}
+
+impl Clock {
+    pub fn new(control: TimeControl) -> Self {
+        Clock {
+            left: [control.base; 2],
+            increment: control.increment,
+            started: None,
+        }
+    }
+
+    /// Starts the clock of the side to move, nothing happens if it is running.
+    pub fn start(&mut self) {
+        self.started.get_or_insert_with(Instant::now);
+    }
+
+    /// Stops the clock after the side (true for white) moved and adds the
+    /// increment, returns false if the time ran out before the move.
+    pub fn stop(&mut self, white: bool) -> bool {
+        let used = self
+            .started
+            .take()
+            .map_or(Duration::ZERO, |started| started.elapsed());
+        let left = &mut self.left[usize::from(!white)];
+        match left.checked_sub(used) {
+            Some(rest) => {
+                *left = rest + self.increment;
+                true
+            }
+            None => {
+                *left = Duration::ZERO;
+                false
+            }
+        }
+    }
+
+    pub fn left(&self, white: bool) -> Duration {
+        self.left[usize::from(!white)]
+    }
+
+    /// Time Ferris can spend on a move of the side.
+    pub fn budget(&self, white: bool) -> Duration {
+        let left = self.left(white);
+        let budget = left / MOVES_TO_GO + self.increment * 3 / 4;
+        // Something is left for answering the next moves
+        budget
+            .min(left.saturating_sub(Duration::from_millis(50)))
+            .max(Duration::from_millis(10))
+    }
+}
+
+fn minutes(time: Duration) -> String {
+    let seconds = time.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+impl Display for Clock {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "White {} | Black {}",
+            minutes(self.left(true)),
+            minutes(self.left(false))
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let control = TimeControl::parse("5+3").unwrap();
+        assert_eq!(control.base, Duration::from_secs(300));
+        assert_eq!(control.increment, Duration::from_secs(3));
+        let control = TimeControl::parse("0.5").unwrap();
+        assert_eq!(control.base, Duration::from_secs(30));
+        assert_eq!(control.increment, Duration::ZERO);
+        assert!(TimeControl::parse("five").is_err());
+        assert!(TimeControl::parse("0+2").is_err());
+        assert!(TimeControl::parse("5+-1").is_err());
+        assert!(TimeControl::parse("1e300").is_err());
+        assert!(TimeControl::parse("5+nan").is_err());
+    }
+
+    #[test]
+    fn test_clock() {
+        let mut clock = Clock::new(TimeControl::parse("1+2").unwrap());
+        assert_eq!(clock.to_string(), "White 1:00 | Black 1:00");
+        clock.start();
+        assert!(clock.stop(true));
+        // Almost no time was used, so the increment is on top of the minute
+        assert!(clock.left(true) > Duration::from_secs(61));
+        assert_eq!(clock.left(false), Duration::from_secs(60));
+        assert_eq!(clock.budget(false), Duration::from_millis(3500));
+
+        // Black thought for a second with a millisecond left
+        clock.left[1] = Duration::from_millis(1);
+        clock.started = Some(Instant::now() - Duration::from_secs(1));
+        assert!(!clock.stop(false));
+        assert_eq!(clock.left(false), Duration::ZERO);
+        assert_eq!(clock.budget(false), Duration::from_millis(10));
+    }
+}
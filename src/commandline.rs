@@ -5,7 +5,14 @@
  * Date: 11.02.2023
  */
 
-static mut COMMANDS: Vec<Command> = Vec::new();
+use crate::board::{Board, ColorMode, Glyphs, MAX_DEPTH};
+use crate::clock::TimeControl;
+use crate::pgn::parse_pgn;
+use crate::themes;
+use std::fs;
+use std::sync::Mutex;
+
+static COMMANDS: Mutex<Vec<Command>> = Mutex::new(Vec::new());
 
 #[allow(dead_code)]
 pub struct Command {
@@ -15,16 +22,15 @@ pub struct Command {
 }
 
 impl Command {
-    #[allow(dead_code)]
+    // Registers the command instead of returning it
+    #[allow(dead_code, clippy::new_ret_no_self)]
     pub fn new(name: &str, description: &str, function: fn()) {
         let command = Command {
             name: name.to_string(),
             description: description.to_string(),
-            function: function,
+            function,
         };
-        unsafe {
-            COMMANDS.push(command);
-        }
+        COMMANDS.lock().unwrap().push(command);
     }
 
     #[allow(dead_code)]
@@ -45,30 +51,261 @@ impl Command {
 
     #[allow(dead_code)]
     pub fn print_help_all() {
-        unsafe {
-            for command in &COMMANDS {
-                command.print_help();
-            }
+        for command in COMMANDS.lock().unwrap().iter() {
+            command.print_help();
         }
     }
 
     #[allow(dead_code)]
     pub fn execute_command(name: &str) {
-        unsafe {
-            for command in &COMMANDS {
-                if command.name == name {
-                    command.execute();
-                    return;
-                }
+        for command in COMMANDS.lock().unwrap().iter() {
+            if command.name == name {
+                command.execute();
+                return;
             }
         }
         println!("Command not found");
     }
 }
 
+pub const USAGE: &str = "\
+Usage: ferris [options]
+
+  --fen <fen>               Start from this position
+  --pgn <file>              Go on with the first game of the file
+  --theme <name>            Colors of the board: rust, gruvbox, gruvbox-dark, cotton-candy, blank
+                            or one of ~/.config/ferris/themes
+  --piece-set <name>        Colors of the pieces: challenger, regular, gruvbox, darcula,
+                            hacker, neon, pastel, solarized or one of ~/.config/ferris/themes
+  --glyphs <set>            Pieces as ascii letters, unicode symbols, filled symbols
+                            or nerd (Nerd Font icons)
+  --wide                    Bigger squares which look square
+  --colors <mode>           truecolor, 256 for terminals without truecolor or none,
+                            guessed from the terminal if it isn't given
+  --no-color                Same as --colors none
+  --flip                    Draw black at the bottom
+  --mode <mode>             human (two players), engine (against Ferris), uci or xboard
+  --side <side>             white or black, the side you play against the engine
+  --depth <plies>           How deep Ferris searches
+  --personality <name>      Ferris comments the game, see datum/Commentary
+  --time-control <min+inc>  Clocks for both sides, like 5+3
+  --uci, --xboard           Same as --mode uci and --mode xboard
+  --help                    Show this";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Human,  // Two players on the same terminal
+    Engine, // Against Ferris
+    Uci,
+    Xboard,
+}
+
+/// Settings of the game given on the command line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub fen: Option<String>,
+    pub pgn: Option<String>, // Path of the game to go on with
+    pub theme: Option<String>,
+    pub piece_set: Option<String>,
+    pub glyphs: Glyphs,
+    pub wide: bool,
+    pub colors: Option<ColorMode>, // None guesses them from the terminal
+    pub flip: bool,
+    pub mode: Mode,
+    pub white: bool, // Side of the player against the engine
+    pub depth: Option<u32>,
+    pub personality: Option<String>,
+    pub time_control: Option<TimeControl>,
+    pub help: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            fen: None,
+            pgn: None,
+            theme: None,
+            piece_set: None,
+            glyphs: Glyphs::Ascii,
+            wide: false,
+            colors: None,
+            flip: false,
+            mode: Mode::Human,
+            white: true,
+            depth: None,
+            personality: None,
+            time_control: None,
+            help: false,
+        }
+    }
+}
+
+impl Options {
+    /// Reads the arguments without the program name, both --depth 4 and --depth=4 work.
+    pub fn parse(args: &[String]) -> Result<Options, String> {
+        let mut options = Options::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let (name, inline) = match arg.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_owned())),
+                None => (arg.as_str(), None),
+            };
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| args.next().cloned())
+                    .ok_or(format!("{} needs a value", name))
+            };
+            match name {
+                "--fen" => options.fen = Some(value()?),
+                "--pgn" => options.pgn = Some(value()?),
+                "--theme" => options.theme = Some(value()?),
+                "--piece-set" => options.piece_set = Some(value()?),
+                "--glyphs" => {
+                    let name = value()?;
+                    options.glyphs =
+                        Glyphs::find(&name).ok_or(format!("Unknown glyphs {}", name))?;
+                }
+                "--wide" => options.wide = true,
+                "--no-color" => options.colors = Some(ColorMode::Plain),
+                "--colors" => {
+                    let name = value()?;
+                    options.colors =
+                        Some(ColorMode::find(&name).ok_or(format!("Unknown colors {}", name))?);
+                }
+                "--flip" => options.flip = true,
+                "--mode" => {
+                    options.mode = match value()?.as_str() {
+                        "human" => Mode::Human,
+                        "engine" => Mode::Engine,
+                        "uci" => Mode::Uci,
+                        "xboard" => Mode::Xboard,
+                        mode => return Err(format!("Unknown mode {}", mode)),
+                    }
+                }
+                "--side" => {
+                    options.white = match value()?.as_str() {
+                        "white" => true,
+                        "black" => false,
+                        side => return Err(format!("Unknown side {}", side)),
+                    }
+                }
+                "--uci" => options.mode = Mode::Uci,
+                "--xboard" => options.mode = Mode::Xboard,
+                "--depth" => {
+                    let depth = value()?;
+                    options.depth = match depth.parse() {
+                        Ok(depth @ 1..=MAX_DEPTH) => Some(depth),
+                        _ => {
+                            return Err(format!(
+                                "Depth has to be 1 to {}, not {}",
+                                MAX_DEPTH, depth
+                            ))
+                        }
+                    }
+                }
+                "--personality" => options.personality = Some(value()?),
+                "--time-control" => options.time_control = Some(TimeControl::parse(&value()?)?),
+                "--help" | "-h" => options.help = true,
+                _ => return Err(format!("Unknown option {}", arg)),
+            }
+        }
+        if options.fen.is_some() && options.pgn.is_some() {
+            return Err("--fen and --pgn can't be used together".to_owned());
+        }
+        Ok(options)
+    }
+
+    /// Board the game starts with, drawn the way the options say.
+    pub fn board(&self) -> Result<Board, String> {
+        let mut board = match (&self.fen, &self.pgn) {
+            (Some(fen), _) => Board::from_fen(fen).map_err(|e| e.to_string())?,
+            (_, Some(path)) => {
+                let text = fs::read_to_string(path)
+                    .map_err(|e| format!("Couldn't read {}: {}", path, e))?;
+                let games = parse_pgn(&text).map_err(|e| e.to_string())?;
+                let game = games.into_iter().next();
+                game.ok_or(format!("There is no game in {}", path))?.board
+            }
+            _ => Board::default(),
+        };
+        if let Some(theme) = &self.theme {
+            themes::set_theme(&mut board, theme)?;
+        }
+        if let Some(piece_set) = &self.piece_set {
+            themes::set_piece_set(&mut board, piece_set)?;
+        }
+        board.glyphs = self.glyphs;
+        board.wide = self.wide;
+        board.colors = self.colors.unwrap_or_else(ColorMode::detect);
+        board.flipped = self.flip;
+        Ok(board)
+    }
+}
+
 #[allow(dead_code)]
 pub fn clear() {
     //print!("{}[2J", 27 as char);
-    print!("{}", "\x1bc");
+    print!("\x1bc");
     //print!("{}", "\r".repeat(100)); // Didn't work TODO
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn parse(args: &str) -> Result<Options, String> {
+        let args: Vec<String> = args.split_whitespace().map(str::to_owned).collect();
+        Options::parse(&args)
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("").unwrap(), Options::default());
+        let options =
+            parse("--mode engine --depth=4 --flip --no-color --time-control 3+2").unwrap();
+        assert_eq!(options.mode, Mode::Engine);
+        assert_eq!(options.depth, Some(4));
+        assert!(options.flip && options.colors == Some(ColorMode::Plain));
+        assert!(options.white); // Flipping only changes the drawing
+        assert!(!parse("--side black").unwrap().white);
+        assert_eq!(
+            options.time_control.unwrap().increment,
+            Duration::from_secs(2)
+        );
+        assert_eq!(parse("--uci").unwrap().mode, Mode::Uci);
+
+        assert_eq!(parse("--depth").unwrap_err(), "--depth needs a value");
+        assert!(parse("--depth 0").is_err());
+        assert!(parse("--mode robot").is_err());
+        assert!(parse("--side red").is_err());
+        assert!(parse("--colour").is_err());
+        assert!(parse("--glyphs emoji").is_err());
+        assert!(parse("--colors 16").is_err());
+        assert!(parse("--fen 8/8/8/8/8/8/8/8 --pgn game.pgn").is_err());
+    }
+
+    #[test]
+    fn test_board() {
+        let args = [
+            "--fen".to_owned(),
+            "4k3/8/8/8/8/8/8/4K3 b - - 0 1".to_owned(),
+            "--theme=gruvbox".to_owned(),
+            "--piece-set".to_owned(),
+            "neon".to_owned(),
+            "--flip".to_owned(),
+            "--glyphs=unicode".to_owned(),
+            "--colors=256".to_owned(),
+        ];
+        let board = Options::parse(&args).unwrap().board().unwrap();
+        assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/4K3 b - - 0 1");
+        assert!(board.flipped && board.colors == ColorMode::Ansi256);
+        assert_eq!(board.glyphs, Glyphs::Unicode);
+        assert_ne!(board.color, Board::default().color);
+        assert_ne!(board.white_color, Board::default().white_color);
+
+        assert!(parse("--theme plaid").unwrap().board().is_err());
+        assert!(parse("--pgn missing.pgn").unwrap().board().is_err());
+    }
+}
@@ -1,9 +1,29 @@
+use crate::board::{ColorMode, Glyphs, SearchLimits, CHESS960_POSITIONS};
 use crate::clear;
+use crate::commentary::Personality;
+use crate::engine::EngineClient;
+use crate::pgn::{parse_pgn, PgnGame};
+use crate::themes;
 use crate::Board;
+use std::fs;
 use std::io::{stdin, Write};
 use std::process::exit;
+use std::time::Instant;
 
-pub fn command(board: &mut Board, raw_coords: String) -> Result<String, ()> {
+/// Takes back up to n moves, returns how many were taken back.
+/// XBoard's undo and remove use it too.
+pub fn undo_moves(board: &mut Board, n: usize) -> usize {
+    (0..n).take_while(|_| board.undo_move().is_some()).count()
+}
+
+/// Runs the command, Ok gives the input back if it isn't one. The engine and
+/// the personality commenting the game are owned by the game loop.
+pub fn command(
+    board: &mut Board,
+    raw_coords: String,
+    engine: &mut Option<EngineClient>,
+    commentator: &mut Option<Personality>,
+) -> Result<String, ()> {
     let turn = board.turn;
     let player = match turn {
         true => "White",
@@ -12,21 +32,41 @@ pub fn command(board: &mut Board, raw_coords: String) -> Result<String, ()> {
     match raw_coords.as_str() {
         "exit" => exit(0),
         "undo" => {
-            board.undo_move();
-            return Err(());
+            if undo_moves(board, 1) == 0 {
+                println!("Nothing to undo");
+            }
+            Err(())
+        }
+        "redo" => {
+            if board.redo_move().is_none() {
+                println!("Nothing to redo");
+            }
+            Err(())
+        }
+        undo if undo.starts_with("undo ") => {
+            // Going back several moves at once: undo <n>
+            match undo[5..].trim().parse::<usize>() {
+                Ok(n) => {
+                    undo_moves(board, n);
+                }
+                Err(_) => println!("Usage: undo <number of moves>"),
+            }
+            Err(())
         }
         "seval" => {
             println!("Evaluation: {}", board.simple_evaluate());
-            return Err(());
+            Err(())
         }
         "eval" => {
-            println!("Evaluation: {}", board.evaluate());
-            return Err(());
+            // Why the position is better for one side, then what the search thinks
+            println!("{}", board.evaluation());
+            println!("Evaluation: {:+.2}", board.evaluate() as f64 / 100.0);
+            Err(())
         }
         "reset" => {
             *board = board.classic();
             clear();
-            return Err(());
+            Err(())
         }
         "resign" => {
             println!("{player} resigned");
@@ -34,11 +74,11 @@ pub fn command(board: &mut Board, raw_coords: String) -> Result<String, ()> {
         }
         "clear" => {
             clear();
-            return Err(());
+            Err(())
         }
         "turn" => {
             println!("Turn: {}", player);
-            return Err(());
+            Err(())
         }
         "pass" => {
             println!("Turn passed");
@@ -59,13 +99,234 @@ pub fn command(board: &mut Board, raw_coords: String) -> Result<String, ()> {
                 }
                 "n" => {
                     println!("Draw declined");
-                    return Err(());
+                    Err(())
                 }
                 _ => {
                     println!("Invalid input");
+                    Err(())
+                }
+            }
+        }
+        "claim" => {
+            // Threefold repetition and the fifty-move rule only end the game when claimed
+            match board.claimable_draw() {
+                Some(result) => {
+                    println!("{player} claimed a draw");
+                    println!("{}", result);
+                    exit(0);
+                }
+                None => {
+                    println!("There is no draw to claim");
+                    Err(())
+                }
+            }
+        }
+        "fen" => {
+            println!("FEN: {}", board.to_fen());
+            Err(())
+        }
+        fen if fen.starts_with("fen ") => {
+            // Loading a position pasted from another tool
+            if let Err(e) = board.set_fen(&fen[4..]) {
+                println!("{}", e);
+            }
+            Err(())
+        }
+        "engine" => {
+            // Analysing the position with the loaded engine
+            let engine = match engine.as_mut() {
+                Some(engine) => engine,
+                None => {
+                    println!("No engine loaded, use engine <path>");
                     return Err(());
                 }
+            };
+            match engine.analyse(board, SearchLimits::default(), |info| println!("{}", info)) {
+                Ok(reply) => println!(
+                    "{} suggests {}",
+                    engine.name,
+                    board.to_san(&reply.best_move)
+                ),
+                Err(e) => println!("{}", e),
             }
+            Err(())
+        }
+        "engine quit" => {
+            match engine.take() {
+                Some(engine) => println!("{} unloaded", engine.name),
+                None => println!("No engine loaded"),
+            }
+            Err(())
+        }
+        start if start.starts_with("engine ") => {
+            // engine <path> [arguments]: starts an external UCI engine
+            match EngineClient::start(&start[7..]) {
+                Ok(started) => {
+                    println!("{} loaded", started.name);
+                    *engine = Some(started);
+                }
+                Err(e) => println!("{}", e),
+            }
+            Err(())
+        }
+        perft if perft.starts_with("perft ") => {
+            // perft <depth>: leaves below every root move and their sum
+            match perft[6..].trim().parse::<u32>() {
+                Ok(depth) => {
+                    let start = Instant::now();
+                    let divide = board.divide(depth);
+                    for (name, nodes) in &divide {
+                        println!("{}: {}", name, nodes);
+                    }
+                    let nodes: u64 = divide.iter().map(|(_, nodes)| nodes).sum();
+                    println!("\nNodes searched: {} ({:.2?})", nodes, start.elapsed());
+                }
+                Err(_) => println!("Usage: perft <depth>"),
+            }
+            Err(())
+        }
+        chess960 if chess960 == "chess960" || chess960.starts_with("chess960 ") => {
+            // chess960 [number]: new game from that start position, a random one without it
+            let position = match chess960[8..].trim() {
+                "" => Some(Board::random_chess960()),
+                number => number.parse().ok().and_then(Board::chess960),
+            };
+            match position {
+                Some(mut position) => {
+                    position.copy_style(board);
+                    *board = position;
+                }
+                None => println!("Usage: chess960 [0-{}]", CHESS960_POSITIONS - 1),
+            }
+            Err(())
+        }
+        "personality" => {
+            match commentator {
+                Some(personality) => println!("Personality: {}", personality.name),
+                None => println!("Personality: off"),
+            }
+            println!("Available: {}", Personality::available().join(", "));
+            Err(())
+        }
+        "personality off" => {
+            *commentator = None;
+            Err(())
+        }
+        personality if personality.starts_with("personality ") => {
+            // personality <name>: Ferris comments the game like datum/Commentary/<name>.toml
+            match Personality::load(personality[12..].trim()) {
+                Ok(personality) => *commentator = Some(personality),
+                Err(e) => println!("{}", e),
+            }
+            Err(())
+        }
+        "theme" => {
+            println!("Board themes: {}", themes::board_theme_names().join(", "));
+            Err(())
+        }
+        theme if theme.starts_with("theme ") => {
+            // theme <name>: colors of the squares, see themes.rs for your own ones
+            if let Err(e) = themes::set_theme(board, theme[6..].trim()) {
+                println!("{}", e);
+            }
+            Err(())
+        }
+        "pieces" => {
+            println!("Piece sets: {}", themes::piece_set_names().join(", "));
+            Err(())
+        }
+        pieces if pieces.starts_with("pieces ") => {
+            if let Err(e) = themes::set_piece_set(board, pieces[7..].trim()) {
+                println!("{}", e);
+            }
+            Err(())
+        }
+        glyphs if glyphs == "glyphs" || glyphs.starts_with("glyphs ") => {
+            // glyphs <ascii|unicode|filled|nerd>: how the pieces are drawn
+            match Glyphs::find(glyphs[6..].trim()) {
+                Some(glyphs) => board.glyphs = glyphs,
+                None => {
+                    let names: Vec<&str> = Glyphs::NAMED.iter().map(|(name, _)| *name).collect();
+                    println!("Usage: glyphs <{}>", names.join("|"));
+                }
+            }
+            Err(())
+        }
+        colors if colors == "colors" || colors.starts_with("colors ") => {
+            // colors <truecolor|256|none>: what the terminal can show
+            match ColorMode::find(colors[6..].trim()) {
+                Some(colors) => board.colors = colors,
+                None => {
+                    let names: Vec<&str> = ColorMode::NAMED.iter().map(|(name, _)| *name).collect();
+                    println!("Usage: colors <{}>", names.join("|"));
+                }
+            }
+            Err(())
+        }
+        "flip" => {
+            // Turning the board by hand stops the automatic flipping
+            board.flipped = !board.flipped;
+            board.auto_flip = false;
+            Err(())
+        }
+        "autoflip" => {
+            board.auto_flip = !board.auto_flip;
+            match board.auto_flip {
+                true => {
+                    println!("The side to move is drawn at the bottom in games between two players")
+                }
+                false => println!("Automatic flipping is off"),
+            }
+            Err(())
+        }
+        "coordinates" => {
+            board.coordinates = !board.coordinates;
+            Err(())
+        }
+        "wide" => {
+            board.wide = !board.wide;
+            Err(())
+        }
+        "themes" => {
+            // Every theme side by side, with the files which couldn't be read
+            print!("{}", themes::preview(board));
+            for error in themes::custom_themes().1 {
+                println!("{}", error);
+            }
+            Err(())
+        }
+        "pgn" => {
+            print!("{}", PgnGame::new(board.clone()).to_pgn());
+            Err(())
+        }
+        save if save.starts_with("save ") => {
+            let path = save[5..].trim();
+            match fs::write(path, PgnGame::new(board.clone()).to_pgn()) {
+                Ok(_) => println!("Game saved to {}", path),
+                Err(e) => println!("Couldn't save the game: {}", e),
+            }
+            Err(())
+        }
+        load if load.starts_with("load ") => {
+            // load <file> [game number]
+            let mut args = load[5..].split_whitespace();
+            let path = args.next().unwrap_or("");
+            let number: usize = args.next().and_then(|n| n.parse().ok()).unwrap_or(1);
+            let games = fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| parse_pgn(&text).map_err(|e| e.to_string()));
+            match games {
+                Ok(games) => match games.into_iter().nth(number.saturating_sub(1)) {
+                    Some(game) => {
+                        let mut loaded = game.board;
+                        loaded.copy_style(board);
+                        *board = loaded;
+                    }
+                    None => println!("There is no game {} in {}", number, path),
+                },
+                Err(e) => println!("Couldn't load the game: {}", e),
+            }
+            Err(())
         }
         _ => Ok(raw_coords),
     }
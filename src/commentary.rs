@@ -0,0 +1,292 @@
+/*
+ * File: commentary.rs
+ * Purpose: Ferris talking about the game with the quotes in datum/Commentary
+ * Date: 18.10.2026
+ * */
+
+/* Every file in datum/Commentary is a personality. Sections are the things
+ * that can happen on the board, keys are lists of quotes:
+ *
+ *  [random]      quotes        nothing special happened
+ *  [capture]     pawn ... queen  the opponent took a piece of Ferris
+ *  [take]        pawn ... queen  Ferris took a piece, rare for any piece
+ *  [check]       quotes        the opponent gave check
+ *  [checked]     quotes        Ferris gave check
+ *  [checkmate]   quotes        the opponent mated Ferris
+ *  [checkmated]  quotes        Ferris mated the opponent
+ *
+ * Files are read with toml.rs. Empty quotes are skipped, so a personality
+ * can stay quiet about something.
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::board::Board;
+use crate::toml;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const COMMENTARY_DIR: &str = "datum/Commentary";
+const RANDOM_CHANCE: u64 = 8; // One in this many quiet moves gets a random quote
+const RARE_CHANCE: u64 = 10; // One in this many takes gets a rare quote
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommentErr(pub String);
+
+impl Display for CommentErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// What the last move did, seen from the side Ferris plays.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    Quiet,
+    Take(char),    // Ferris took this piece (lowercase)
+    Capture(char), // The opponent took this piece of Ferris (lowercase)
+    Check,         // The opponent gave check
+    Checked,       // Ferris gave check
+    Checkmate,     // The opponent mated Ferris
+    Checkmated,    // Ferris mated the opponent
+}
+
+impl Event {
+    /// Event of the last played move, None if nothing was played.
+    pub fn last_move(board: &Board, ferris: bool) -> Option<Event> {
+        let entry = board.history.last()?;
+        let by_ferris = board.turn != ferris; // The mover is not to move anymore
+        let event = if board.is_checkmate() {
+            match by_ferris {
+                true => Event::Checkmated,
+                false => Event::Checkmate,
+            }
+        } else if board.is_check() {
+            match by_ferris {
+                true => Event::Checked,
+                false => Event::Check,
+            }
+        } else if entry.taken != ' ' {
+            let taken = entry.taken.to_ascii_lowercase();
+            match by_ferris {
+                true => Event::Take(taken),
+                false => Event::Capture(taken),
+            }
+        } else {
+            Event::Quiet
+        };
+        Some(event)
+    }
+}
+
+fn piece_name(piece: char) -> &'static str {
+    match piece {
+        'p' => "pawn",
+        'n' => "knight",
+        'b' => "bishop",
+        'r' => "rook",
+        'q' => "queen",
+        _ => "king",
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Personality {
+    pub name: String,
+    sections: HashMap<String, HashMap<String, Vec<String>>>,
+    seed: u64, // Xorshift state for picking quotes
+}
+
+impl Personality {
+    /// Reads a personality from its TOML text.
+    pub fn parse(name: &str, text: &str) -> Result<Personality, CommentErr> {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64);
+        Ok(Personality {
+            name: name.to_owned(),
+            sections: parse_sections(text)?,
+            seed: seed | 1, // Xorshift never leaves zero
+        })
+    }
+
+    /// Loads datum/Commentary/<name>.toml, or the file itself if a path is given.
+    pub fn load(name: &str) -> Result<Personality, CommentErr> {
+        let path = match name.ends_with(".toml") {
+            true => PathBuf::from(name),
+            false => Path::new(COMMENTARY_DIR).join(format!("{}.toml", name)),
+        };
+        let name = path
+            .file_stem()
+            .map_or(name.into(), |stem| stem.to_string_lossy());
+        let text = fs::read_to_string(&path)
+            .map_err(|e| CommentErr(format!("Couldn't read {}: {}", path.display(), e)))?;
+        Personality::parse(&name, &text)
+            .map_err(|e| CommentErr(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Names of the personalities in datum/Commentary.
+    pub fn available() -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(COMMENTARY_DIR)
+            .map(|dir| {
+                dir.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+                    .filter_map(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+
+    /// Quotes under [section] key, empty if there are none.
+    pub fn quotes(&self, section: &str, key: &str) -> &[String] {
+        self.sections
+            .get(section)
+            .and_then(|keys| keys.get(key))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    fn random(&mut self, below: u64) -> u64 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        self.seed % below
+    }
+
+    fn pick(&mut self, section: &str, key: &str) -> Option<String> {
+        let count = self.quotes(section, key).len();
+        if count == 0 {
+            return None;
+        }
+        let i = self.random(count as u64) as usize;
+        Some(self.quotes(section, key)[i].clone())
+    }
+
+    /// A quote fitting the event, None if the personality has nothing to say.
+    pub fn comment(&mut self, event: Event) -> Option<String> {
+        match event {
+            Event::Quiet if self.random(RANDOM_CHANCE) == 0 => self.pick("random", "quotes"),
+            Event::Quiet => None,
+            Event::Take(piece) => match self.random(RARE_CHANCE) {
+                0 => self.pick("take", "rare"),
+                _ => None,
+            }
+            .or_else(|| self.pick("take", piece_name(piece))),
+            Event::Capture(piece) => self.pick("capture", piece_name(piece)),
+            Event::Check => self.pick("check", "quotes"),
+            Event::Checked => self.pick("checked", "quotes"),
+            Event::Checkmate => self.pick("checkmate", "quotes"),
+            // Older personalities only know about being mated
+            Event::Checkmated => self
+                .pick("checkmated", "quotes")
+                .or_else(|| self.pick("checked", "quotes")),
+        }
+    }
+}
+
+// Quotes of every key, sections keep only what is written in them
+fn parse_sections(text: &str) -> Result<HashMap<String, HashMap<String, Vec<String>>>, CommentErr> {
+    let table = toml::parse(text).map_err(|e| CommentErr(e.0))?;
+    Ok(table
+        .into_iter()
+        .map(|(section, keys)| {
+            let keys = keys.into_iter().map(|(key, value)| (key, value.strings()));
+            (section, keys.collect())
+        })
+        .collect())
+}
+
+/// Comment of the chosen personality on the last move, Ferris playing `ferris` (true for white).
+/// Without a personality Ferris stays quiet.
+pub fn comment(
+    commentator: &mut Option<Personality>,
+    board: &Board,
+    ferris: bool,
+) -> Option<String> {
+    let event = Event::last_move(board, ferris)?;
+    commentator.as_mut()?.comment(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Move;
+
+    const TEXT: &str = r#"
+[random] # anything
+quotes = ["Ha Ha", 'single']
+
+[take]
+queen = [
+	"NOM NOM", # tasty
+	"",
+]
+rare = ["Rare"]
+
+[checked]
+quotes = [
+	"CHECK!",
+]
+"#;
+
+    #[test]
+    fn test_parse() {
+        let personality = Personality::parse("test", TEXT).unwrap();
+        assert_eq!(personality.quotes("random", "quotes"), ["Ha Ha", "single"]);
+        assert_eq!(personality.quotes("take", "queen"), ["NOM NOM"]);
+        assert!(personality.quotes("take", "pawn").is_empty());
+        assert!(personality.quotes("capture", "queen").is_empty());
+
+        let error = Personality::parse("test", "[random]\nquotes = [\"a\" \"b\"]").unwrap_err();
+        assert_eq!(error.0, "line 2: Expected , or ]");
+        assert!(Personality::parse("test", "quotes = [\"open").is_err());
+    }
+
+    #[test]
+    fn test_every_personality_loads() {
+        let names = Personality::available();
+        assert!(names.contains(&"pirate".to_owned()));
+        for name in names {
+            let personality = Personality::load(&name).unwrap();
+            assert!(personality.sections.contains_key("take"), "{}", name);
+        }
+        assert!(Personality::load("nobody").is_err());
+    }
+
+    #[test]
+    fn test_events() {
+        let mut board = Board::from_fen("4k3/8/8/3q4/4P3/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(Event::last_move(&board, false), None);
+        board.move_piece(Move::new([5, 5], [4, 4])).unwrap(); // exd5
+        assert_eq!(Event::last_move(&board, false), Some(Event::Capture('q')));
+        assert_eq!(Event::last_move(&board, true), Some(Event::Take('q')));
+
+        let mut board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        board.move_piece(Move::new([1, 8], [1, 2])).unwrap(); // Ra7
+        assert_eq!(Event::last_move(&board, true), Some(Event::Quiet));
+        board.undo_move();
+        board.move_piece(Move::new([1, 8], [1, 1])).unwrap(); // Ra8#
+        assert_eq!(Event::last_move(&board, true), Some(Event::Checkmated));
+        assert_eq!(Event::last_move(&board, false), Some(Event::Checkmate));
+    }
+
+    #[test]
+    fn test_comment() {
+        let mut personality = Personality::parse("test", TEXT).unwrap();
+        for _ in 0..20 {
+            let quote = personality.comment(Event::Take('q')).unwrap();
+            assert!(quote == "NOM NOM" || quote == "Rare", "{}", quote);
+        }
+        // No pawn quotes, only a rare one now and then
+        let quote = personality.comment(Event::Take('p'));
+        assert!(quote.is_none_or(|quote| quote == "Rare"));
+        // Falls back to the check quotes without a checkmated section
+        assert_eq!(
+            personality.comment(Event::Checkmated),
+            Some("CHECK!".to_owned())
+        );
+        assert_eq!(personality.comment(Event::Check), None);
+    }
+}
@@ -0,0 +1,402 @@
+/* File: engine.rs
+ * Purpuse: Running external UCI engines as child processes
+ * Date: 18.10.2026
+ * */
+
+/* Ferris can play against or analyse with any engine that speaks UCI.
+ * The engine is started as a child process, a thread reads its stdout and
+ * hands the lines over a channel so waiting for an answer can time out:
+ *
+ *  Ferris                           engine
+ *  uci                         ->
+ *                              <-   id name Stockfish
+ *                              <-   uciok
+ *  isready                     ->
+ *                              <-   readyok
+ *  position fen ... moves e2e4 ->
+ *  go movetime 5000            ->
+ *                              <-   info depth 5 score cp -20 pv e7e5 g1f3
+ *                              <-   bestmove e7e5
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::board::{square_name, Board, Move, SearchLimits};
+use crate::parser::{parse_move, ParseErr};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const MOVE_GRACE: Duration = Duration::from_secs(5); // Extra time given to answer a go
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineErr(pub String);
+
+impl Display for EngineErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "EngineErr: {}", self.0)
+    }
+}
+
+impl Error for EngineErr {}
+
+/// What the engine told about its search in an info line.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EngineInfo {
+    pub depth: u32,
+    pub score: Option<i32>, // Centipawns for the side to move
+    pub mate: Option<i32>,  // Moves until mate, negative if the side to move gets mated
+    pub pv: Vec<String>,
+}
+
+impl EngineInfo {
+    // Only lines with a score are interesting, the rest are progress reports
+    fn parse(line: &str) -> Option<EngineInfo> {
+        let mut info = EngineInfo::default();
+        let mut words = line.split_whitespace().skip(1);
+        while let Some(word) = words.next() {
+            match word {
+                "depth" => info.depth = words.next()?.parse().ok()?,
+                "score" => match (words.next()?, words.next()?.parse().ok()?) {
+                    ("cp", cp) => info.score = Some(cp),
+                    ("mate", moves) => info.mate = Some(moves),
+                    _ => return None,
+                },
+                "pv" => {
+                    info.pv = words.map(str::to_owned).collect();
+                    break;
+                }
+                _ => (),
+            }
+        }
+        (info.score.is_some() || info.mate.is_some()).then_some(info)
+    }
+}
+
+impl Display for EngineInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (self.mate, self.score) {
+            (Some(moves), _) => write!(f, "depth {} mate {}", self.depth, moves)?,
+            (None, Some(cp)) => write!(f, "depth {} score {:+.2}", self.depth, cp as f64 / 100.0)?,
+            (None, None) => write!(f, "depth {}", self.depth)?,
+        }
+        write!(f, " pv {}", self.pv.join(" "))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineReply {
+    pub best_move: Move,
+    pub info: Option<EngineInfo>, // Last info line with a score
+}
+
+pub struct EngineClient {
+    pub name: String,
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    chess960: bool, // Value of UCI_Chess960 the engine was told
+}
+
+impl EngineClient {
+    /// Starts the engine, command is the path of the binary and its arguments.
+    pub fn start(command: &str) -> Result<EngineClient, EngineErr> {
+        let mut words = command.split_whitespace();
+        let program = words
+            .next()
+            .ok_or(EngineErr("no engine given".to_owned()))?;
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| EngineErr(format!("couldn't start {}: {}", program, e)))?;
+
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            // Stops when the engine exits or nobody is listening anymore
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut engine = EngineClient {
+            name: program.to_owned(),
+            child,
+            stdin,
+            lines,
+            chess960: false,
+        };
+        engine.send("uci")?;
+        let mut name = None;
+        engine.wait_for("uciok", Some(HANDSHAKE_TIMEOUT), |line| {
+            if let Some(id) = line.strip_prefix("id name ") {
+                name = Some(id.trim().to_owned());
+            }
+        })?;
+        if let Some(name) = name {
+            engine.name = name;
+        }
+        engine.is_ready()?;
+        Ok(engine)
+    }
+
+    fn send(&mut self, line: &str) -> Result<(), EngineErr> {
+        writeln!(self.stdin, "{}", line)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| EngineErr(format!("{} stopped listening: {}", self.name, e)))
+    }
+
+    // Reads lines until one starts with the expected word, returns that line
+    fn wait_for(
+        &mut self,
+        expected: &str,
+        timeout: Option<Duration>,
+        mut on_line: impl FnMut(&str),
+    ) -> Result<String, EngineErr> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            let line = match deadline {
+                Some(deadline) => {
+                    let left = deadline.saturating_duration_since(Instant::now());
+                    self.lines.recv_timeout(left)
+                }
+                None => self
+                    .lines
+                    .recv()
+                    .map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match line {
+                Ok(line) if line.split_whitespace().next() == Some(expected) => return Ok(line),
+                Ok(line) => on_line(&line),
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(EngineErr(format!(
+                        "{} didn't send {} in time",
+                        self.name, expected
+                    )))
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(EngineErr(format!("{} exited", self.name)))
+                }
+            }
+        }
+    }
+
+    pub fn is_ready(&mut self) -> Result<(), EngineErr> {
+        self.send("isready")?;
+        self.wait_for("readyok", Some(HANDSHAKE_TIMEOUT), |_| ())?;
+        Ok(())
+    }
+
+    #[allow(dead_code)]
+    pub fn new_game(&mut self) -> Result<(), EngineErr> {
+        self.send("ucinewgame")?;
+        self.is_ready()
+    }
+
+    /// Sends the game of the board, from its starting position with every played move.
+    pub fn set_position(&mut self, board: &Board) -> Result<(), EngineErr> {
+        // Castling moves are sent as the king taking its rook in Chess960
+        if board.chess960 != self.chess960 {
+            self.send(&format!(
+                "setoption name UCI_Chess960 value {}",
+                board.chess960
+            ))?;
+            self.chess960 = board.chess960;
+        }
+        let mut start = board.clone();
+        let mut moves = Vec::new();
+        while let Some(entry) = start.unmake_move() {
+            let (from, to) = entry.played.decode_move();
+            let mut name = square_name(from) + &square_name(to);
+            name.extend(entry.played.promotion());
+            moves.push(name);
+        }
+        moves.reverse();
+
+        let mut position = format!("position fen {}", start.to_fen());
+        if !moves.is_empty() {
+            position = format!("{} moves {}", position, moves.join(" "));
+        }
+        self.send(&position)
+    }
+
+    /// Asks the engine for the best move of the board within the limits,
+    /// every info line with a score is given to on_info while it thinks.
+    pub fn analyse(
+        &mut self,
+        board: &Board,
+        limits: SearchLimits,
+        mut on_info: impl FnMut(&EngineInfo),
+    ) -> Result<EngineReply, EngineErr> {
+        self.set_position(board)?;
+        // The depth of Ferris would only hold a stronger engine back when there is time
+        let go = match limits.time {
+            Some(time) => format!("go movetime {}", time.as_millis()),
+            None => format!("go depth {}", limits.depth),
+        };
+        self.send(&go)?;
+
+        let mut last_info = None;
+        let timeout = limits.time.map(|time| time + MOVE_GRACE);
+        let line = self.wait_for("bestmove", timeout, |line| {
+            if let Some(info) = line
+                .starts_with("info ")
+                .then(|| EngineInfo::parse(line))
+                .flatten()
+            {
+                on_info(&info);
+                last_info = Some(info);
+            }
+        })?;
+
+        let best = line.split_whitespace().nth(1).unwrap_or("(none)");
+        let best_move = parse_move(board, best).map_err(|e| {
+            let kind = match e {
+                ParseErr::Illegal(_) => "illegal",
+                _ => "invalid",
+            };
+            EngineErr(format!("{} sent an {} move: {}", self.name, kind, best))
+        })?;
+        Ok(EngineReply {
+            best_move,
+            info: last_info,
+        })
+    }
+}
+
+impl Drop for EngineClient {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        // Giving the engine a moment to leave on its own
+        for _ in 0..20 {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::{env, fs, process};
+
+    // Stand-in engine which always wants to play the same move, its
+    // directory in the temp dir is removed when it is dropped
+    struct Stub {
+        dir: PathBuf,
+        command: String,
+    }
+
+    impl Stub {
+        fn new(name: &str, best: &str) -> Stub {
+            let script = format!(
+                "while read line; do\n\
+                 case \"$line\" in\n\
+                 uci) echo \"id name Stub\"; echo \"option name Hash\"; echo uciok;;\n\
+                 isready) echo readyok;;\n\
+                 position*) echo \"$line\" > \"$0.position\";;\n\
+                 go*) echo \"$line\" > \"$0.go\"; echo \"info depth 1 nodes 10\"; echo \"info depth 2 score cp 31 pv {best}\"; echo \"bestmove {best}\";;\n\
+                 quit) exit 0;;\n\
+                 esac\n\
+                 done\n"
+            );
+            let dir = env::temp_dir().join(format!("ferris-{}-{}", name, process::id()));
+            fs::create_dir_all(&dir).unwrap();
+            let path = dir.join("engine.sh");
+            fs::write(&path, script).unwrap();
+            Stub {
+                command: format!("sh {}", path.display()),
+                dir,
+            }
+        }
+
+        // Last position or go command the engine got
+        fn received(&self, command: &str) -> String {
+            let path = self.dir.join(format!("engine.sh.{}", command));
+            fs::read_to_string(path).unwrap().trim().to_owned()
+        }
+    }
+
+    impl Drop for Stub {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    #[test]
+    fn test_handshake_and_analyse() {
+        let stub = Stub::new("analyse", "e7e5");
+        let mut engine = EngineClient::start(&stub.command).unwrap();
+        assert_eq!(engine.name, "Stub");
+
+        let mut board = Board::default();
+        let e4 = parse_move(&board, "e4").unwrap();
+        board.move_piece(e4).unwrap();
+        let mut infos = Vec::new();
+        let reply = engine
+            .analyse(&board, SearchLimits::default(), |info| {
+                infos.push(info.clone())
+            })
+            .unwrap();
+        assert_eq!(reply.best_move, Move::new([5, 2], [5, 4]));
+        assert_eq!(infos.len(), 1);
+        assert_eq!(
+            reply.info.unwrap().to_string(),
+            "depth 2 score +0.31 pv e7e5"
+        );
+
+        assert_eq!(
+            stub.received("position"),
+            "position fen rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 moves e2e4"
+        );
+        assert_eq!(stub.received("go"), "go movetime 5000");
+
+        // Depth is only sent without a time limit
+        let limits = SearchLimits {
+            depth: 3,
+            time: None,
+        };
+        engine.analyse(&board, limits, |_| ()).unwrap();
+        assert_eq!(stub.received("go"), "go depth 3");
+    }
+
+    #[test]
+    fn test_illegal_reply() {
+        let stub = Stub::new("illegal", "e2e5");
+        let mut engine = EngineClient::start(&stub.command).unwrap();
+        let err = engine
+            .analyse(&Board::default(), SearchLimits::default(), |_| ())
+            .unwrap_err();
+        assert_eq!(err.0, "Stub sent an illegal move: e2e5");
+    }
+
+    #[test]
+    fn test_missing_engine() {
+        assert!(EngineClient::start("/nonexistent/engine").is_err());
+        assert!(EngineClient::start("").is_err());
+    }
+
+    #[test]
+    fn test_info_parsing() {
+        let info = EngineInfo::parse("info depth 7 seldepth 9 score mate -3 nodes 5 pv e2e4 e7e5")
+            .unwrap();
+        assert_eq!(info.depth, 7);
+        assert_eq!(info.mate, Some(-3));
+        assert_eq!(info.pv, vec!["e2e4", "e7e5"]);
+        assert_eq!(EngineInfo::parse("info string hello"), None);
+    }
+}
@@ -5,19 +5,33 @@
  * */
 
 mod board;
+mod clock;
 mod commandline;
-mod parser;
 mod commands;
+mod commentary;
+mod engine;
+mod parser;
+mod pgn;
+mod themes;
+mod toml;
+mod uci;
+mod xboard;
 
 use board::Board;
-use board::Move;
-use board::MoveType;
+use board::{GameResult, SearchLimits, TranspositionTable, MAX_DEPTH};
+use clock::Clock;
 use commandline::*;
-use parser::convert_to_coords;
 use commands::command;
+use commentary::Personality;
+use engine::EngineClient;
+use parser::parse_move;
 
 use std::{
-    io::{stdin, Write},
+    env,
+    io::{stdin, stdout, Write},
+    process::exit,
+    sync::{atomic::AtomicBool, Arc, Mutex},
+    time::Duration,
 };
 
 /*
@@ -26,68 +40,307 @@ use std::{
  *  27 25 -> Errenous move
  * */
 
-static mut MOVE_TYPE: String = String::new();
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Player {
+    Human,
+    Ferris,   // Built in search
+    External, // Engine loaded with the engine command
+}
+
+/* Commands about the engine need the state of the game loop,
+ * returns false if the input is not one of them. */
+fn engine_command(
+    raw: &str,
+    players: &mut [Player; 2],
+    limits: &mut SearchLimits,
+    table: &mut TranspositionTable,
+) -> bool {
+    let mut args = raw.split_whitespace();
+    match (args.next(), args.next()) {
+        (Some("play"), Some(side)) => {
+            // Side played by the engine: play <side> [engine]
+            let engine = match args.next() {
+                Some("engine") => Player::External,
+                _ => Player::Ferris,
+            };
+            *players = match side {
+                "white" => [engine, Player::Human],
+                "black" => [Player::Human, engine],
+                "both" => [engine, engine],
+                "none" => [Player::Human, Player::Human],
+                _ => {
+                    println!("Usage: play <white|black|both|none> [engine]");
+                    *players
+                }
+            };
+        }
+        (Some("depth"), Some(depth)) => match depth.parse() {
+            // Without a single ply the search has no move to play
+            Ok(depth @ 1..=MAX_DEPTH) => limits.depth = depth,
+            _ => println!("Usage: depth <1-{}>", MAX_DEPTH),
+        },
+        (Some("movetime"), Some(secs)) => match secs.parse().map(Duration::try_from_secs_f64) {
+            Ok(Ok(time)) => limits.time = Some(time),
+            _ => println!("Usage: movetime <seconds>"),
+        },
+        (Some("hash"), Some(megabytes)) => match megabytes.parse() {
+            Ok(megabytes) => *table = TranspositionTable::new(megabytes),
+            Err(_) => println!("Usage: hash <megabytes>"),
+        },
+        _ => return false,
+    }
+    true
+}
+
+// Plays the best move the engine finds
+fn engine_move(
+    board: &mut Board,
+    player: Player,
+    limits: SearchLimits,
+    table: &mut TranspositionTable,
+    engine: &mut Option<EngineClient>,
+    last_move: &mut String,
+) -> Result<(), String> {
+    let (best_move, about) = match player {
+        Player::External => {
+            let engine = engine
+                .as_mut()
+                .ok_or("No engine loaded, use engine <path>")?;
+            let reply = engine
+                .analyse(board, limits, |_| ())
+                .map_err(|e| e.to_string())?;
+            let about = match reply.info {
+                Some(info) => format!("{}, {}", engine.name, info),
+                None => engine.name.clone(),
+            };
+            (reply.best_move, about)
+        }
+        _ => {
+            let stop = Arc::new(AtomicBool::new(false));
+            let result = board.search_with(limits, stop, table, |_| ());
+            let best_move = result.best_move.ok_or("No legal moves")?;
+            let about = format!("depth {}, score {}", result.depth, result.score);
+            (best_move, about)
+        }
+    };
+    let san = board.to_san(&best_move);
+    let move_t = board.move_piece(best_move).map_err(|e| e.to_string())?;
+    *last_move = format!("{} ({}, {})", san, move_t, about);
+    Ok(())
+}
+
+// Stops the clock of the side which just moved, false if it lost on time
+fn stop_clock(clock: &mut Option<Clock>, board: &Board) -> bool {
+    let mover = !board.turn;
+    let in_time = clock.as_mut().is_none_or(|clock| clock.stop(mover));
+    if !in_time {
+        let player = if mover { "White" } else { "Black" };
+        println!("{} lost on time", player);
+    }
+    in_time
+}
+
+// Side Ferris plays for the commentary, black unless only white is played by an engine
+fn ferris_side(players: &[Player; 2]) -> bool {
+    players[0] != Player::Human && players[1] == Player::Human
+}
+
+// Prints what the chosen personality says about the last move
+fn commentate(commentator: &mut Option<Personality>, board: &Board, players: &[Player; 2]) {
+    if let Some(quote) = commentary::comment(commentator, board, ferris_side(players)) {
+        println!("Ferris: {}", quote);
+    }
+}
+
+// Plays the move the player typed, returns false if nothing was played
+fn human_move(
+    board: &mut Board,
+    raw_coords: String,
+    engine: &mut Option<EngineClient>,
+    commentator: &mut Option<Personality>,
+    last_move: &mut String,
+) -> bool {
+    let raw_coords = match command(board, raw_coords, engine, commentator) {
+        Ok(raw_coords) => raw_coords,
+        _ => {
+            return false;
+        }
+    };
+
+    // e8=N and e7e8n choose the piece, a pawn promotes to a queen otherwise
+    let current_move = match parse_move(board, &raw_coords) {
+        Ok(parsed) => parsed, // move is a reserved keyword
+        Err(e) => {
+            println!("{}", e);
+            return false;
+        }
+    };
+    let move_t = board.move_piece(current_move);
+    match move_t {
+        Ok(move_t) => {
+            let san = board.san_history().pop().unwrap_or_default();
+            *last_move = format!("{} ({})", san, move_t);
+            //clear();
+            true
+        }
+        Err(e) => {
+            println!("{}", e);
+            false
+        }
+    }
+}
 
 fn main() {
-    //let mut board: Board = Board::from_fen(
-        //"        P                                              p        ".to_owned(),
-    //);
-    let mut board = Board::default();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = match Options::parse(&args) {
+        Ok(options) => options,
+        Err(e) => {
+            println!("{}\n\n{}", e, USAGE);
+            exit(2);
+        }
+    };
+    if options.help {
+        println!("{}", USAGE);
+        return;
+    }
+    match options.mode {
+        Mode::Uci => return uci::run(stdin().lock(), Arc::new(Mutex::new(stdout()))),
+        Mode::Xboard => return xboard::run(stdin().lock(), stdout()),
+        _ => (),
+    }
+
+    let mut board = match options.board() {
+        Ok(board) => board,
+        Err(e) => {
+            println!("{}", e);
+            exit(2);
+        }
+    };
+    let mut last_move = String::new();
+    let mut players = match (options.mode, options.white) {
+        (Mode::Engine, true) => [Player::Human, Player::Ferris], // [white, black]
+        (Mode::Engine, false) => [Player::Ferris, Player::Human],
+        _ => [Player::Human, Player::Human],
+    };
+    let mut limits = SearchLimits::default();
+    if let Some(depth) = options.depth {
+        limits.depth = depth;
+    }
+    let mut table = TranspositionTable::default(); // Kept between the moves of Ferris
+    let mut engine: Option<EngineClient> = None; // Loaded with the engine command
+    let mut clock = options.time_control.map(Clock::new);
+
+    // Ferris comments the game from the start with --personality
+    let mut commentator = None;
+    if let Some(name) = &options.personality {
+        match Personality::load(name) {
+            Ok(personality) => commentator = Some(personality),
+            Err(e) => println!("{}", e),
+        }
+    }
 
     loop {
-        board.draw_ascii();
-        unsafe {
-            println!("Move type: {}", MOVE_TYPE);
+        // Engine moves on its own turns without waiting for the input
+        let side = usize::from(!board.turn);
+        let mut player = players[side];
+        if let Some(clock) = &mut clock {
+            clock.start();
         }
-        print!(">> ");
-        std::io::stdout().flush().unwrap();
-        let mut raw_coords = String::new();
-
-        stdin()
-            .read_line(&mut raw_coords)
-            .expect("failed to readline");
-        raw_coords = raw_coords.trim().to_string();
-
-        let raw_coords = match command(&mut board, raw_coords) {
-            Ok(raw_coords) => {
-                raw_coords
+        if player == Player::Human {
+            if board.auto_flip && players == [Player::Human, Player::Human] {
+                board.flipped = !board.turn;
             }
-            _ => {
-                continue;
+            board.draw_ascii();
+            println!("Last move: {}", last_move);
+            if let Some(clock) = &clock {
+                println!("{}", clock);
             }
-        };
+            if board.is_check() {
+                println!("Check!");
+            }
+            if let Some(draw) = board.claimable_draw() {
+                let rule = match draw {
+                    GameResult::ThreefoldRepetition => "threefold repetition",
+                    _ => "the fifty-move rule",
+                };
+                println!("You can claim a draw by {}, type claim to take it", rule);
+            }
+            print!(">> ");
+            std::io::stdout().flush().unwrap();
+            let mut raw_coords = String::new();
+
+            let read = stdin()
+                .read_line(&mut raw_coords)
+                .expect("failed to readline");
+            if read == 0 {
+                break; // End of input
+            }
+            raw_coords = raw_coords.trim().to_string();
 
-        if let Ok(coords) = convert_to_coords(&raw_coords) {
-            let current_move = Move::new(coords[0], coords[1]); // move is a reserved keyword
-            let move_t = board.move_piece(current_move);
-            match move_t {
-                Ok(move_t) => {
-                    if move_t == MoveType::Promotion {
-                        println!("Promote to (q/r/b/n): ");
-                        std::io::stdout().flush().unwrap();
-                        let mut promote = String::new();
-                        stdin()
-                            .read_line(&mut promote)
-                            .expect("failed to readline");
-                        let promote:char = promote.trim().chars().nth(0).unwrap();
-                        match board.promote_piece(promote) {
-                            Err(_) => {
-                                println!("Enter a valid piece");
-                                board.undo_move();
-                                continue;
-                            }
-                            _ => {}
-                        }
+            if raw_coords == "xboard" {
+                // XBoard GUIs start engines in this mode
+                xboard::run(stdin().lock(), stdout());
+                return;
+            }
+            if engine_command(&raw_coords, &mut players, &mut limits, &mut table) {
+                continue;
+            }
+            // go: engine plays the next move of the side to move
+            if raw_coords != "go" {
+                let played = human_move(
+                    &mut board,
+                    raw_coords,
+                    &mut engine,
+                    &mut commentator,
+                    &mut last_move,
+                );
+                if played {
+                    if !stop_clock(&mut clock, &board) {
+                        break;
                     }
-                    unsafe {
-                        MOVE_TYPE = move_t.to_string();
+                    commentate(&mut commentator, &board, &players);
+                    if let Some(result) = board.result() {
+                        board.draw_ascii();
+                        println!("{}", result);
+                        break;
                     }
-                    //clear();
-                }
-                Err(e) => {
-                    println!("{}", e);
                 }
+                continue;
             }
+            player = Player::Ferris;
+        }
+
+        // Ferris thinks as long as its clock allows
+        let limits = match &clock {
+            Some(clock) => SearchLimits {
+                time: Some(clock.budget(board.turn)),
+                ..limits
+            },
+            None => limits,
+        };
+        let played = engine_move(
+            &mut board,
+            player,
+            limits,
+            &mut table,
+            &mut engine,
+            &mut last_move,
+        );
+        if let Err(e) = played {
+            // Giving the side back to the player, so the game can go on
+            println!("{}", e);
+            players[side] = Player::Human;
+            continue;
+        }
+        if !stop_clock(&mut clock, &board) {
+            break;
+        }
+        commentate(&mut commentator, &board, &players);
+        if let Some(result) = board.result() {
+            board.draw_ascii();
+            println!("Last move: {}", last_move);
+            println!("{}", result);
+            break;
         }
     }
 }
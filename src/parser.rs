@@ -1,41 +1,298 @@
 /* File: parser.rs
  * Purpuse: Alowing taking a user input and converting it to coordinates.
- * eg. e4 -> [5, 7], [5, 5] (from, to) coordinates. (<< I will do this later 
- * because it is harder compared to simple coordinates)
+ * Accepts simple coordinates (5755), long algebraic notation (e2e4, e7e8q)
+ * and standard algebraic notation (e4, Nf3, exd5, O-O, e8=Q+).
  * Author: KoBruhh
  * Date: 05.02.2023
  * */
 
-use std::{error::Error, fmt::{Display}};
+use crate::board::{parse_square, Board, Move};
+use std::{error::Error, fmt::Display};
 
-//const PARSE_ERR:&str = r#"Invalid input! Expected integers as input! (Spaces are ignored)"#;
-
-#[derive(Debug)]
-pub struct ParseErr;
+#[derive(Debug, PartialEq)]
+pub enum ParseErr {
+    Invalid,           // Input doesn't look like a move at all
+    Illegal(String),   // Input is a move but there is no such legal move
+    Ambiguous(String), // More than one piece can make the move
+}
 
 impl Error for ParseErr {}
 impl Display for ParseErr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "ParseErr: Failed to parse input!")
+        match self {
+            ParseErr::Invalid => write!(f, "ParseErr: Failed to parse input!"),
+            ParseErr::Illegal(san) => write!(f, "ParseErr: {} is not a legal move!", san),
+            ParseErr::Ambiguous(san) => write!(
+                f,
+                "ParseErr: {} is ambiguous! Add the file or rank of the piece (eg. Nbd7)",
+                san
+            ),
+        }
     }
 }
 
-pub fn convert_to_coords(usr: &str) -> Result<[[usize;2]; 2], ParseErr> {
-    let usr = usr.replace(" ", "");
+pub fn convert_to_coords(usr: &str) -> Result<[[usize; 2]; 2], ParseErr> {
+    let usr = usr.replace(' ', "");
     let usr = usr.trim();
     if usr.len() != 4 {
-        return Err(ParseErr);
+        return Err(ParseErr::Invalid);
     }
     let mut result = [[0; 2]; 2];
-    for i in 0..2 {
-        for j in 0..2 {
-            if let Some(num) = usr.chars().nth(i*2+j).unwrap().to_digit(10) {
-                result[i][j] = num as usize;
-            } else {
-                return Err(ParseErr);
-            }
+    for (i, ch) in usr.chars().enumerate() {
+        match ch.to_digit(10) {
+            Some(num) => result[i / 2][i % 2] = num as usize,
+            None => return Err(ParseErr::Invalid),
         }
     }
-    
     Ok(result)
 }
+
+/// Converts any supported notation to a move of the given position.
+/// The promotion piece is part of the move if the input has one.
+pub fn parse_move(board: &Board, usr: &str) -> Result<Move, ParseErr> {
+    let usr = usr.trim();
+    if let Ok(coords) = convert_to_coords(usr) {
+        return legal_move(board, usr, Move::new(coords[0], coords[1]));
+    }
+    if let Some(parsed) = parse_long_algebraic(usr) {
+        return legal_move(board, usr, parsed);
+    }
+    parse_san(board, usr)
+}
+
+// Coordinates name the squares but say nothing about legality, e7e8 promotes to a queen
+fn legal_move(board: &Board, usr: &str, parsed: Move) -> Result<Move, ParseErr> {
+    let queen = parsed.clone().with_promotion('q');
+    board
+        .legal_moves()
+        .into_iter()
+        .find(|m| *m == parsed || (parsed.promotion().is_none() && *m == queen))
+        .ok_or(ParseErr::Illegal(usr.to_owned()))
+}
+
+fn promotion_piece(ch: char) -> Option<char> {
+    match ch.to_ascii_lowercase() {
+        'q' | 'r' | 'b' | 'n' => Some(ch.to_ascii_lowercase()),
+        _ => None,
+    }
+}
+
+// e2e4, e7e8q
+fn parse_long_algebraic(usr: &str) -> Option<Move> {
+    if !usr.is_ascii() || (usr.len() != 4 && usr.len() != 5) {
+        return None;
+    }
+    let from = parse_square(&usr[0..2])?;
+    let to = parse_square(&usr[2..4])?;
+    match usr.chars().nth(4) {
+        Some(ch) => Some(Move::new(from, to).with_promotion(promotion_piece(ch)?)),
+        None => Some(Move::new(from, to)),
+    }
+}
+
+/* Standard algebraic notation only names the piece and the target square,
+ * so the moving piece is found by looking at the legal moves:
+ *
+ *   Nbd7   -> knight from the b file goes to d7
+ *   exd5   -> pawn from the e file takes on d5
+ *   e8=Q+  -> pawn goes to e8 and becomes a queen, giving check
+ *   O-O-O  -> long castle
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+fn parse_san(board: &Board, usr: &str) -> Result<Move, ParseErr> {
+    // Check, mate and annotation marks don't change the move
+    let san = usr.trim_end_matches(['+', '#', '!', '?']);
+    if san.is_empty() || !san.is_ascii() {
+        return Err(ParseErr::Invalid);
+    }
+
+    let legal = board.legal_moves();
+    let piece_at = |square: [usize; 2]| board.board[square[1] - 1][square[0] - 1];
+
+    if let Some(king_side) = match san {
+        "O-O" | "0-0" => Some(true),
+        "O-O-O" | "0-0-0" => Some(false),
+        _ => None,
+    } {
+        return legal
+            .into_iter()
+            .find(|m| board.castling_side(m) == Some(king_side))
+            .ok_or(ParseErr::Illegal(usr.to_owned()));
+    }
+
+    // Promotion can be written as e8=Q or e8Q
+    let (san, promotion) = match san.split_once('=') {
+        Some((rest, piece)) if piece.len() == 1 => {
+            let piece = piece.chars().next().and_then(promotion_piece);
+            (rest, Some(piece.ok_or(ParseErr::Invalid)?))
+        }
+        Some(_) => return Err(ParseErr::Invalid),
+        None => match san.chars().last().filter(char::is_ascii_uppercase) {
+            Some(last) if san.len() > 2 && promotion_piece(last).is_some() => {
+                (&san[..san.len() - 1], promotion_piece(last))
+            }
+            _ => (san, None),
+        },
+    };
+
+    let (piece, rest) = match san.chars().next() {
+        Some(ch @ ('N' | 'B' | 'R' | 'Q' | 'K')) => (ch.to_ascii_lowercase(), &san[1..]),
+        _ => ('p', san),
+    };
+    if rest.len() < 2 {
+        return Err(ParseErr::Invalid);
+    }
+    let to = parse_square(&rest[rest.len() - 2..]).ok_or(ParseErr::Invalid)?;
+    let hint = &rest[..rest.len() - 2];
+    let capture = hint.ends_with('x');
+    let hint = hint.trim_end_matches('x');
+
+    // Whatever is left before the target square narrows down the moving piece
+    let (mut file, mut rank) = (None, None);
+    for ch in hint.chars() {
+        match ch {
+            'a'..='h' if file.is_none() => file = Some((ch as u8 - b'a') as usize + 1),
+            '1'..='8' if rank.is_none() => rank = Some(9 - ch.to_digit(10).unwrap() as usize),
+            _ => return Err(ParseErr::Invalid),
+        }
+    }
+    // A pawn without a capture can only go straight
+    if piece == 'p' && !capture && file.is_none() {
+        file = Some(to[0]);
+    }
+    if promotion.is_some() && piece != 'p' {
+        return Err(ParseErr::Invalid);
+    }
+
+    let candidates: Vec<Move> = legal
+        .into_iter()
+        .filter(|m| {
+            let (from, target) = m.decode_move();
+            target == to
+                && piece_at(from).to_ascii_lowercase() == piece
+                && file.is_none_or(|file| from[0] == file)
+                && rank.is_none_or(|rank| from[1] == rank)
+                // e8 alone promotes to a queen
+                && match m.promotion() {
+                    Some(promoted) => promoted == promotion.unwrap_or('q'),
+                    None => promotion.is_none(),
+                }
+        })
+        .collect();
+
+    match candidates.len() {
+        0 => Err(ParseErr::Illegal(usr.to_owned())),
+        1 => Ok(candidates[0].clone()),
+        _ => Err(ParseErr::Ambiguous(usr.to_owned())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(fen: &str, usr: &str) -> Result<Move, ParseErr> {
+        parse_move(&Board::from_fen(fen).unwrap(), usr)
+    }
+
+    const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    #[test]
+    fn test_coordinates() {
+        assert_eq!(convert_to_coords("5755"), Ok([[5, 7], [5, 5]]));
+        assert_eq!(convert_to_coords("57 55"), Ok([[5, 7], [5, 5]]));
+        assert_eq!(convert_to_coords("575"), Err(ParseErr::Invalid));
+        assert_eq!(convert_to_coords("e2e4"), Err(ParseErr::Invalid));
+    }
+
+    #[test]
+    fn test_long_algebraic() {
+        let e4 = Move::new([5, 7], [5, 5]);
+        assert_eq!(parse(START, "e2e4"), Ok(e4.clone()));
+        assert_eq!(parse(START, "5755"), Ok(e4));
+        assert_eq!(parse(START, "e7e8k"), Err(ParseErr::Invalid));
+        for illegal in ["e2e5", "5754", "e7e8q", "e7e5", "a1h8"] {
+            assert_eq!(
+                parse(START, illegal),
+                Err(ParseErr::Illegal(illegal.to_owned()))
+            );
+        }
+
+        let fen = "4k3/1P6/8/8/8/8/8/4K3 w - - 0 1";
+        let b8 = Move::new([2, 2], [2, 1]);
+        assert_eq!(parse(fen, "b7b8"), Ok(b8.clone().with_promotion('q')));
+        assert_eq!(parse(fen, "b7b8n"), Ok(b8.with_promotion('n')));
+        assert_eq!(
+            parse(fen, "e1e2q"),
+            Err(ParseErr::Illegal("e1e2q".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_san() {
+        assert_eq!(parse(START, "e4"), Ok(Move::new([5, 7], [5, 5])));
+        assert_eq!(parse(START, "Nf3"), Ok(Move::new([7, 8], [6, 6])));
+        assert_eq!(parse(START, "Nf3!?"), Ok(Move::new([7, 8], [6, 6])));
+        assert_eq!(parse(START, "e5"), Err(ParseErr::Illegal("e5".to_owned())));
+        assert_eq!(
+            parse(START, "Nd4"),
+            Err(ParseErr::Illegal("Nd4".to_owned()))
+        );
+        assert_eq!(parse(START, "hello"), Err(ParseErr::Invalid));
+        assert_eq!(parse(START, ""), Err(ParseErr::Invalid));
+
+        let fen = "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2";
+        assert_eq!(parse(fen, "exd5"), Ok(Move::new([5, 5], [4, 4])));
+    }
+
+    #[test]
+    fn test_san_disambiguation() {
+        let fen = "4k3/8/8/8/8/8/8/RN2K1NR w - - 0 1";
+        assert_eq!(parse(fen, "Ne2"), Ok(Move::new([7, 8], [5, 7])));
+        assert_eq!(parse(fen, "Nd2"), Ok(Move::new([2, 8], [4, 7])));
+
+        let fen = "4k3/8/8/8/8/8/3K4/1N3N2 w - - 0 1";
+        assert_eq!(parse(fen, "Nd2"), Err(ParseErr::Illegal("Nd2".to_owned())));
+        assert_eq!(parse(fen, "Nc3"), Ok(Move::new([2, 8], [3, 6])));
+        let fen = "4k3/8/8/8/8/8/8/1N1K1N2 w - - 0 1";
+        assert_eq!(
+            parse(fen, "Nd2"),
+            Err(ParseErr::Ambiguous("Nd2".to_owned()))
+        );
+        assert_eq!(parse(fen, "Nbd2"), Ok(Move::new([2, 8], [4, 7])));
+        assert_eq!(parse(fen, "Nfd2"), Ok(Move::new([6, 8], [4, 7])));
+
+        let fen = "4k3/8/8/8/R7/8/8/R3K3 w - - 0 1";
+        assert_eq!(
+            parse(fen, "Ra2"),
+            Err(ParseErr::Ambiguous("Ra2".to_owned()))
+        );
+        assert_eq!(parse(fen, "R1a2"), Ok(Move::new([1, 8], [1, 7])));
+        assert_eq!(parse(fen, "R4xa2"), Ok(Move::new([1, 5], [1, 7])));
+    }
+
+    #[test]
+    fn test_san_castling_and_promotion() {
+        let fen = "r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1";
+        assert_eq!(parse(fen, "O-O"), Ok(Move::new([5, 8], [7, 8])));
+        assert_eq!(parse(fen, "0-0-0"), Ok(Move::new([5, 8], [3, 8])));
+        assert_eq!(
+            parse(fen, "bxa8=N+"),
+            Ok(Move::new([2, 2], [1, 1]).with_promotion('n'))
+        );
+        assert_eq!(
+            parse(fen, "b8Q"),
+            Ok(Move::new([2, 2], [2, 1]).with_promotion('q'))
+        );
+        assert_eq!(parse(fen, "b8=K"), Err(ParseErr::Invalid));
+        assert_eq!(
+            parse(fen, "b8"),
+            Ok(Move::new([2, 2], [2, 1]).with_promotion('q'))
+        );
+
+        let fen = "r3k2r/8/8/8/8/8/8/R2QK2R w KQkq - 0 1";
+        assert_eq!(
+            parse(fen, "O-O-O"),
+            Err(ParseErr::Illegal("O-O-O".to_owned()))
+        );
+    }
+}
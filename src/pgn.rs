@@ -0,0 +1,455 @@
+/* File: pgn.rs
+ * Purpuse: Saving games as Portable Game Notation and loading them back
+ * Date: 18.10.2026
+ * */
+
+/* A PGN game has a tag section and the movetext:
+ *
+ *  [Event "Casual game"]
+ *  [Site "Ferris"]
+ *  [Date "2023.02.11"]
+ *  [Round "-"]
+ *  [White "?"]
+ *  [Black "?"]
+ *  [Result "1-0"]
+ *
+ *  1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6?? 4. Qxf7# 1-0
+ *
+ * While loading, comments ({...} and ; till the end of the line),
+ * variations (...) and NAGs ($1) are skipped, they don't change the game.
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::board::Board;
+use crate::parser::parse_move;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::iter::Peekable;
+use std::str::Chars;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SEVEN_TAG_ROSTER: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+const RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+const LINE_WIDTH: usize = 80; // PGN lines should be shorter than this
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnErr(pub String);
+
+impl Display for PgnErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PgnErr: {}", self.0)
+    }
+}
+
+impl Error for PgnErr {}
+
+#[derive(Debug, Clone)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>, // In the order they are written
+    pub board: Board,                // Final position, played moves are in its history
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Tag(String, String),
+    Move(String),
+    Result(String),
+}
+
+// Today as YYYY.MM.DD
+fn today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    // Days since 1970-01-01 to a civil date (Howard Hinnant's algorithm)
+    let z = (secs / 86400) as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}.{:02}.{:02}", year, month, day)
+}
+
+impl PgnGame {
+    /// Wraps a board with the Seven Tag Roster filled with defaults.
+    pub fn new(board: Board) -> PgnGame {
+        let mut game = PgnGame {
+            tags: Vec::new(),
+            board,
+        };
+        let date = today();
+        let defaults = ["Casual game", "Ferris", &date, "-", "?", "?", "*"];
+        for (name, value) in SEVEN_TAG_ROSTER.iter().zip(defaults) {
+            game.set_tag(name, value);
+        }
+        game
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old)) => *old = value.to_owned(),
+            None => self.tags.push((name.to_owned(), value.to_owned())),
+        }
+    }
+
+    /// Result of the game, a finished board overrides the Result tag.
+    pub fn result(&self) -> String {
+        match self.board.result() {
+            Some(result) => result.score().to_owned(),
+            None => self.tag("Result").unwrap_or("*").to_owned(),
+        }
+    }
+
+    pub fn to_pgn(&self) -> String {
+        let result = self.result();
+        let mut start = self.board.clone();
+        while start.unmake_move().is_some() {}
+        let start_fen = start.to_fen();
+
+        let mut tags = self.tags.clone();
+        tags.retain(|(name, _)| name != "SetUp" && name != "FEN" && name != "Variant");
+        if start.chess960 {
+            tags.push(("Variant".to_owned(), "Chess960".to_owned()));
+        }
+        if start_fen != Board::default().to_fen() || start.chess960 {
+            // Game didn't start from the usual position
+            tags.push(("SetUp".to_owned(), "1".to_owned()));
+            tags.push(("FEN".to_owned(), start_fen));
+        }
+
+        let mut pgn = String::new();
+        for (name, value) in &tags {
+            let value = match name.as_str() {
+                "Result" => result.clone(),
+                _ => value.replace('\\', "\\\\").replace('"', "\\\""),
+            };
+            pgn.push_str(&format!("[{} \"{}\"]\n", name, value));
+        }
+        pgn.push('\n');
+
+        let mut line = String::new();
+        let moves = self.board.move_list();
+        let words = moves
+            .iter()
+            .flat_map(|numbered| numbered.split(' '))
+            .chain([result.as_str()]);
+        for word in words {
+            if !line.is_empty() && line.len() + word.len() + 1 > LINE_WIDTH {
+                pgn.push_str(&line);
+                pgn.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        pgn.push_str(&line);
+        pgn.push('\n');
+        pgn
+    }
+}
+
+// [Event "Open [rapid]"], a ] inside the quotes doesn't end the tag
+fn read_tag(chars: &mut Peekable<Chars>) -> Result<Token, PgnErr> {
+    let skip_whitespace = |chars: &mut Peekable<Chars>| {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    };
+    skip_whitespace(chars);
+    let mut name = String::new();
+    while let Some(ch) = chars.next_if(|&c| !c.is_whitespace() && c != '"' && c != ']') {
+        name.push(ch);
+    }
+    if name.is_empty() {
+        return Err(PgnErr("tag has no name".to_owned()));
+    }
+    skip_whitespace(chars);
+    if chars.next_if_eq(&'"').is_none() {
+        return Err(PgnErr(format!("tag {} has no quoted value", name)));
+    }
+    let mut value = String::new();
+    loop {
+        match chars.next() {
+            Some('"') => break,
+            // Only \" and \\ are escapes, other backslashes stay
+            Some('\\') => value.push(chars.next_if(|&c| c == '"' || c == '\\').unwrap_or('\\')),
+            Some(ch) => value.push(ch),
+            None => return Err(PgnErr(format!("tag {} is never closed", name))),
+        }
+    }
+    skip_whitespace(chars);
+    match chars.next() {
+        Some(']') => Ok(Token::Tag(name, value)),
+        _ => Err(PgnErr(format!("tag {} is never closed", name))),
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, PgnErr> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut depth = 0; // How deep we are inside variations
+    let mut line_start = true;
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\n' => {
+                line_start = true;
+                continue;
+            }
+            '%' if line_start => {
+                // Escaped line, ignored till the end
+                chars.by_ref().find(|&c| c == '\n');
+            }
+            '{' => {
+                if chars.by_ref().all(|c| c != '}') {
+                    return Err(PgnErr("comment is never closed".to_owned()));
+                }
+            }
+            ';' => {
+                chars.by_ref().find(|&c| c == '\n');
+            }
+            '(' => depth += 1,
+            ')' => {
+                if depth == 0 {
+                    return Err(PgnErr("unexpected ')'".to_owned()));
+                }
+                depth -= 1;
+            }
+            '[' if depth == 0 => tokens.push(read_tag(&mut chars)?),
+            _ if ch.is_whitespace() => (),
+            _ => {
+                let mut word = String::from(ch);
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || "{}();[".contains(next) {
+                        break;
+                    }
+                    word.push(next);
+                    chars.next();
+                }
+                if depth == 0 {
+                    tokens.extend(word_to_token(&word));
+                }
+            }
+        }
+        line_start = false;
+    }
+    if depth != 0 {
+        return Err(PgnErr("variation is never closed".to_owned()));
+    }
+    Ok(tokens)
+}
+
+// Splits move numbers from moves: "12." "12..." "12.e4" -> e4
+fn word_to_token(word: &str) -> Option<Token> {
+    if RESULTS.contains(&word) {
+        return Some(Token::Result(word.to_owned()));
+    }
+    if word.starts_with('$') {
+        return None; // NAG
+    }
+    let san = word.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+    if san.is_empty() {
+        None
+    } else {
+        Some(Token::Move(san.to_owned()))
+    }
+}
+
+fn play_game(
+    tags: Vec<(String, String)>,
+    moves: &[String],
+    number: usize,
+) -> Result<PgnGame, PgnErr> {
+    let chess960 = tags.iter().any(|(name, value)| {
+        let value = value.to_ascii_lowercase();
+        name == "Variant" && (value.contains("960") || value.contains("fischer"))
+    });
+    let mut board = Board {
+        chess960,
+        ..Board::default()
+    };
+    if let Some((_, fen)) = tags.iter().find(|(name, _)| name == "FEN") {
+        board
+            .set_fen_chess960(fen, chess960)
+            .map_err(|e| PgnErr(format!("game {}: {}", number, e)))?;
+    }
+    for san in moves {
+        let err = |reason: String| PgnErr(format!("game {}, move {}: {}", number, san, reason));
+        let current_move = parse_move(&board, san).map_err(|e| err(e.to_string()))?;
+        board.move_piece(current_move).map_err(|e| err(e.0))?;
+    }
+    Ok(PgnGame { tags, board })
+}
+
+/// Reads every game of a PGN file.
+pub fn parse_pgn(text: &str) -> Result<Vec<PgnGame>, PgnErr> {
+    let mut games = Vec::new();
+    let mut tags = Vec::new();
+    let mut moves: Vec<String> = Vec::new();
+    let mut in_movetext = false;
+
+    for token in tokenize(text)? {
+        match token {
+            Token::Tag(name, value) => {
+                if in_movetext {
+                    // Game without a result, the next one already started
+                    let game = play_game(std::mem::take(&mut tags), &moves, games.len() + 1)?;
+                    games.push(game);
+                    moves.clear();
+                    in_movetext = false;
+                }
+                tags.push((name, value));
+            }
+            Token::Move(san) => {
+                in_movetext = true;
+                moves.push(san);
+            }
+            Token::Result(result) => {
+                let mut game = play_game(std::mem::take(&mut tags), &moves, games.len() + 1)?;
+                if game.tag("Result").is_none() {
+                    game.set_tag("Result", &result);
+                }
+                games.push(game);
+                moves.clear();
+                in_movetext = false;
+            }
+        }
+    }
+    if in_movetext || !tags.is_empty() {
+        games.push(play_game(tags, &moves, games.len() + 1)?);
+    }
+    Ok(games)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHOLARS_MATE: &str = r#"[Event "Casual game"]
+[Site "Ferris"]
+[Date "2023.02.11"]
+[Round "-"]
+[White "KoBruhh"]
+[Black "Ferris"]
+[Result "1-0"]
+
+1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6 4. Qxf7# 1-0
+"#;
+
+    #[test]
+    fn test_export() {
+        let games = parse_pgn(SCHOLARS_MATE).unwrap();
+        assert_eq!(games.len(), 1);
+        assert!(games[0].board.is_checkmate());
+        assert_eq!(games[0].to_pgn(), SCHOLARS_MATE);
+    }
+
+    #[test]
+    fn test_brackets_and_escapes_in_tags() {
+        let pgn = "[Event \"Open [rapid]\"]\n[Site \"\\\"Ferris\\\" \\\\ home\"]\n[Result \"1-0\"]\n\n1. e4 1-0\n";
+        let games = parse_pgn(pgn).unwrap();
+        assert_eq!(games[0].tag("Event"), Some("Open [rapid]"));
+        assert_eq!(games[0].tag("Site"), Some("\"Ferris\" \\ home"));
+        assert_eq!(games[0].result(), "1-0");
+        assert_eq!(games[0].board.to_fen().split(' ').nth(1), Some("b"));
+    }
+
+    #[test]
+    fn test_new_game_tags() {
+        let game = PgnGame::new(Board::default());
+        let names: Vec<&str> = game.tags.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, SEVEN_TAG_ROSTER);
+        assert_eq!(game.result(), "*");
+        assert_eq!(game.tag("Date").unwrap().len(), 10);
+        assert!(game.to_pgn().ends_with("\n\n*\n"));
+    }
+
+    #[test]
+    fn test_comments_variations_and_nags() {
+        let pgn = "[White \"a\"]\n[Black \"b\"]\n\n\
+                   1. e4 {best by test} e5 (1... c5 2. Nf3 (2. c3) d6) 2. Nf3 $1 ; main line\n\
+                   2... Nc6 3.Bb5 a6!? 1/2-1/2";
+        let games = parse_pgn(pgn).unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(
+            games[0].board.san_history(),
+            vec!["e4", "e5", "Nf3", "Nc6", "Bb5", "a6"]
+        );
+        assert_eq!(games[0].tag("Result"), Some("1/2-1/2"));
+    }
+
+    #[test]
+    fn test_multiple_games() {
+        let pgn = format!(
+            "{}\n{}\n[Event \"Second\"]\n\n1. d4 d5 *\n",
+            SCHOLARS_MATE, SCHOLARS_MATE
+        );
+        let games = parse_pgn(&pgn).unwrap();
+        assert_eq!(games.len(), 3);
+        assert_eq!(games[2].tag("Event"), Some("Second"));
+        assert_eq!(games[2].board.san_history(), vec!["d4", "d5"]);
+    }
+
+    #[test]
+    fn test_fen_tag_and_long_games() {
+        let pgn = "[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/8/R3K3 w Q - 0 1\"]\n\n1. O-O-O Kf7 *";
+        let games = parse_pgn(pgn).unwrap();
+        assert_eq!(games[0].board.to_fen(), "8/5k2/8/8/8/8/8/2KR4 w - - 2 2");
+        assert!(games[0]
+            .to_pgn()
+            .contains("[FEN \"4k3/8/8/8/8/8/8/R3K3 w Q - 0 1\"]"));
+
+        let mut board = Board::default();
+        for _ in 0..10 {
+            for usr in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+                let m = parse_move(&board, usr).unwrap();
+                board.move_piece(m).unwrap();
+            }
+        }
+        let pgn = PgnGame::new(board).to_pgn();
+        assert!(pgn.lines().all(|line| line.len() <= LINE_WIDTH));
+        let games = parse_pgn(&pgn).unwrap();
+        assert_eq!(games[0].board.history.len(), 40);
+    }
+
+    #[test]
+    fn test_chess960_game() {
+        let fen = "rk2r3/pppppppp/8/8/8/8/PPPPPPPP/RK2R3 w KQkq - 0 1";
+        let mut board = Board::from_fen(fen).unwrap();
+        for usr in ["a4", "a5", "O-O-O", "O-O-O"] {
+            let m = parse_move(&board, usr).unwrap();
+            board.move_piece(m).unwrap();
+        }
+        let pgn = PgnGame::new(board.clone()).to_pgn();
+        assert!(pgn.contains("[Variant \"Chess960\"]"));
+        assert!(pgn.contains(&format!("[FEN \"{}\"]", fen)));
+        assert!(pgn.contains("1. a4 a5 2. O-O-O O-O-O"));
+
+        let games = parse_pgn(&pgn).unwrap();
+        assert!(games[0].board.chess960);
+        assert_eq!(games[0].board.to_fen(), board.to_fen());
+    }
+
+    #[test]
+    fn test_invalid_pgn() {
+        assert!(parse_pgn("1. e4 {never closed").is_err());
+        assert!(parse_pgn("1. e4 (1. d4").is_err());
+        assert!(parse_pgn("[Event \"Open] 1. e4").is_err());
+        assert!(parse_pgn("[Event Open] 1. e4").is_err());
+        let err = parse_pgn("1. e4 e5 2. Ke3 *").unwrap_err();
+        assert_eq!(
+            err.0,
+            "game 1, move Ke3: ParseErr: Ke3 is not a legal move!"
+        );
+    }
+}
@@ -0,0 +1,381 @@
+/*
+ * File: themes.rs
+ * Purpose: Choosing board themes and piece sets by name, with the user's own ones
+ * Date: 18.10.2026
+ * */
+
+/* Built in themes are in board/color.rs and board/piece.rs. More can be
+ * written to ~/.config/ferris/themes/<name>.toml ($XDG_CONFIG_HOME/ferris/themes
+ * if it is set). Colors are hex or RGB:
+ *
+ *  [board]                 squares, in the same order as color::themes
+ *  dark = "#db3400"
+ *  light = [210, 191, 181]
+ *
+ *  [pieces]                pieces, like piece::themes
+ *  white = "#fff"
+ *  black = [0, 0, 0]
+ *
+ * A file can have one of the sections or both, the name of the file is the
+ * name of the theme. A user theme with the name of a built in one wins.
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::board::{
+    board_themes, piece_themes, BColor, BTheme, Board, Color, Glyphs, PieceTheme, Renderer,
+    StringRenderer,
+};
+use crate::toml::{self, Value};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const PREVIEWS_PER_ROW: usize = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomTheme {
+    pub name: String,
+    pub board: Option<BTheme>,
+    pub pieces: Option<PieceTheme>,
+}
+
+/// Directory of the user's themes, None if there is no home directory.
+pub fn themes_dir() -> Option<PathBuf> {
+    let config = match env::var_os("XDG_CONFIG_HOME") {
+        Some(config) if !config.is_empty() => PathBuf::from(config),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(config.join("ferris").join("themes"))
+}
+
+// "#rrggbb", "#rgb" or [r, g, b]
+fn parse_color(value: &Value) -> Result<BColor, String> {
+    match value {
+        Value::String(hex) => {
+            let digits = hex.trim_start_matches('#');
+            let channel = |i: usize, width: usize| {
+                let part = digits.get(i * width..(i + 1) * width)?;
+                let value = u8::from_str_radix(part, 16).ok()?;
+                Some(if width == 1 { value * 17 } else { value }) // f is ff
+            };
+            let width = match digits.len() {
+                3 => 1,
+                6 => 2,
+                _ => 0,
+            };
+            match (
+                width,
+                channel(0, width),
+                channel(1, width),
+                channel(2, width),
+            ) {
+                (1 | 2, Some(r), Some(g), Some(b)) => Ok((r, g, b)),
+                _ => Err(format!("{} is not a hex color like #db3400", hex)),
+            }
+        }
+        Value::Array(channels) => {
+            let channels: Vec<u8> = channels
+                .iter()
+                .filter_map(|channel| match channel {
+                    Value::Integer(channel) => u8::try_from(*channel).ok(),
+                    _ => None,
+                })
+                .collect();
+            match channels[..] {
+                [r, g, b] if channels.len() == 3 => Ok((r, g, b)),
+                _ => Err("RGB colors are three numbers from 0 to 255".to_owned()),
+            }
+        }
+        Value::Integer(_) => Err("Colors are hex strings or [r, g, b]".to_owned()),
+    }
+}
+
+/// Reads a theme file, the name comes from the file name.
+pub fn parse_theme(name: &str, text: &str) -> Result<CustomTheme, String> {
+    let table = toml::parse(text).map_err(|e| e.0)?;
+    // Both keys of the section or nothing if the section isn't there
+    let pair = |section: &str, first: &str, second: &str| -> Result<Option<BTheme>, String> {
+        let keys = match table.get(section) {
+            Some(keys) => keys,
+            None => return Ok(None),
+        };
+        let color = |key: &str| match keys.get(key) {
+            Some(value) => parse_color(value).map_err(|e| format!("[{}] {}: {}", section, key, e)),
+            None => Err(format!("[{}] needs {} and {}", section, first, second)),
+        };
+        Ok(Some((color(first)?, color(second)?)))
+    };
+    let theme = CustomTheme {
+        name: name.to_owned(),
+        board: pair("board", "dark", "light")?,
+        pieces: pair("pieces", "white", "black")?,
+    };
+    match (theme.board, theme.pieces) {
+        (None, None) => Err("A theme needs a [board] or [pieces] section".to_owned()),
+        _ => Ok(theme),
+    }
+}
+
+// Theme of the file <dir>/<name>.toml, None if there is no such file
+fn load_from(dir: &Path, name: &str) -> Option<Result<CustomTheme, String>> {
+    let path = dir.join(format!("{}.toml", name));
+    let text = fs::read_to_string(&path).ok()?;
+    Some(parse_theme(name, &text).map_err(|e| format!("{}: {}", path.display(), e)))
+}
+
+// Every theme in the directory, and the errors of the files which couldn't be read
+fn custom_from(dir: &Path) -> (Vec<CustomTheme>, Vec<String>) {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+                .filter_map(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    let (mut themes, mut errors) = (Vec::new(), Vec::new());
+    for result in names.iter().filter_map(|name| load_from(dir, name)) {
+        match result {
+            Ok(theme) => themes.push(theme),
+            Err(e) => errors.push(e),
+        }
+    }
+    (themes, errors)
+}
+
+/// The user's themes, and the errors of the files which couldn't be read.
+pub fn custom_themes() -> (Vec<CustomTheme>, Vec<String>) {
+    match themes_dir() {
+        Some(dir) => custom_from(&dir),
+        None => (Vec::new(), Vec::new()),
+    }
+}
+
+// Looks the name up in the user's themes first, then in the built in ones
+fn find<T: Copy>(
+    name: &str,
+    kind: &str,
+    part: fn(&CustomTheme) -> Option<T>,
+    built_in: fn(&str) -> Option<T>,
+    names: fn() -> Vec<String>,
+) -> Result<T, String> {
+    if let Some(custom) = themes_dir().and_then(|dir| load_from(&dir, name)) {
+        let custom = custom?;
+        return part(&custom).ok_or(format!("Theme {} has no {}", name, kind));
+    }
+    built_in(name).ok_or_else(|| {
+        let names = names().join(", ");
+        format!("Unknown {} {}, choose one of: {}", kind, name, names)
+    })
+}
+
+/// Names of the board themes, built in ones first.
+pub fn board_theme_names() -> Vec<String> {
+    let built_in = board_themes::NAMED.iter().map(|(name, _)| name.to_string());
+    let custom = custom_themes()
+        .0
+        .into_iter()
+        .filter(|theme| theme.board.is_some());
+    let mut names: Vec<String> = built_in.collect();
+    for theme in custom {
+        if !names.contains(&theme.name) {
+            names.push(theme.name);
+        }
+    }
+    names
+}
+
+/// Names of the piece sets, built in ones first.
+pub fn piece_set_names() -> Vec<String> {
+    let built_in = piece_themes::NAMED.iter().map(|(name, _)| name.to_string());
+    let custom = custom_themes()
+        .0
+        .into_iter()
+        .filter(|theme| theme.pieces.is_some());
+    let mut names: Vec<String> = built_in.collect();
+    for theme in custom {
+        if !names.contains(&theme.name) {
+            names.push(theme.name);
+        }
+    }
+    names
+}
+
+/// Colors the squares with the theme of that name.
+pub fn set_theme(board: &mut Board, name: &str) -> Result<(), String> {
+    let theme = find(
+        name,
+        "board theme",
+        |theme| theme.board,
+        board_themes::find,
+        board_theme_names,
+    )?;
+    board.set_color(theme.into());
+    Ok(())
+}
+
+/// Colors the pieces with the piece set of that name.
+pub fn set_piece_set(board: &mut Board, name: &str) -> Result<(), String> {
+    let (white, black) = find(
+        name,
+        "piece set",
+        |theme| theme.pieces,
+        piece_themes::find,
+        piece_set_names,
+    )?;
+    board.white_color = white;
+    board.black_color = black;
+    Ok(())
+}
+
+// Two ranks of pieces on the squares, with the name above them
+fn sample(
+    renderer: &dyn Renderer,
+    name: &str,
+    squares: BTheme,
+    pieces: PieceTheme,
+    glyphs: Glyphs,
+) -> Vec<String> {
+    let rank = |row: usize, pieces: &str, color: BColor| {
+        let mut line = String::new();
+        for (file, piece) in pieces.chars().enumerate() {
+            let square = match (row + file) % 2 {
+                0 => squares.0,
+                _ => squares.1,
+            };
+            line += &renderer.paint(
+                &format!(" {} ", glyphs.glyph(piece)),
+                Color::from(square),
+                Color::from(color),
+            );
+        }
+        line
+    };
+    vec![
+        format!("{:<24}", name),
+        rank(0, "rnbqkbnr", pieces.1),
+        rank(1, "PPPPPPPP", pieces.0),
+    ]
+}
+
+// Samples next to each other, PREVIEWS_PER_ROW of them in a row
+fn side_by_side(samples: &[Vec<String>]) -> String {
+    let mut text = String::new();
+    for row in samples.chunks(PREVIEWS_PER_ROW) {
+        for line in 0..3 {
+            let columns: Vec<&str> = row.iter().map(|sample| sample[line].as_str()).collect();
+            text += &columns.join("  ");
+            text.push('\n');
+        }
+    }
+    text
+}
+
+/// Every board theme with the pieces of the board and every piece set on
+/// the squares of the board.
+pub fn preview(board: &Board) -> String {
+    // Painted in the colors the terminal can show
+    let renderer = StringRenderer::new(board.colors);
+    if !renderer.colored() {
+        // Colors can't be shown, only the names
+        return format!(
+            "Board themes: {}\nPiece sets: {}\n",
+            board_theme_names().join(", "),
+            piece_set_names().join(", ")
+        );
+    }
+    let squares = board.color.rgb();
+    let squares = (squares.0.rgb(), squares.1.rgb());
+    let pieces = (board.white_color, board.black_color);
+    let mut preview = board.clone();
+
+    let mut boards = Vec::new();
+    for name in board_theme_names() {
+        if set_theme(&mut preview, &name).is_ok() {
+            let theme = preview.color.rgb();
+            boards.push(sample(
+                &renderer,
+                &name,
+                (theme.0.rgb(), theme.1.rgb()),
+                pieces,
+                board.glyphs,
+            ));
+        }
+    }
+    let mut sets = Vec::new();
+    for name in piece_set_names() {
+        if set_piece_set(&mut preview, &name).is_ok() {
+            let set = (preview.white_color, preview.black_color);
+            sets.push(sample(&renderer, &name, squares, set, board.glyphs));
+        }
+    }
+    format!(
+        "Board themes:\n{}\nPiece sets:\n{}",
+        side_by_side(&boards),
+        side_by_side(&sets)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::ColorMode;
+
+    #[test]
+    fn test_parse_theme() {
+        let text = "[board]\ndark = \"#db3400\"\nlight = [210, 191, 181]\n[pieces]\nwhite = '#fff'\nblack = [0, 0, 0]\n";
+        let theme = parse_theme("mine", text).unwrap();
+        assert_eq!(theme.board, Some(((219, 52, 0), (210, 191, 181))));
+        assert_eq!(theme.pieces, Some(((255, 255, 255), (0, 0, 0))));
+
+        let theme = parse_theme("mine", "[pieces]\nwhite = '#ffffff'\nblack = '#000'").unwrap();
+        assert_eq!(theme.board, None);
+
+        let error = parse_theme("mine", "[board]\ndark = '#12345'\nlight = '#fff'").unwrap_err();
+        assert_eq!(
+            error,
+            "[board] dark: #12345 is not a hex color like #db3400"
+        );
+        assert!(parse_theme("mine", "[board]\ndark = [256, 0, 0]\nlight = '#fff'").is_err());
+        assert!(parse_theme("mine", "[board]\ndark = '#fff'").is_err());
+        assert!(parse_theme("mine", "[colors]\ndark = '#fff'").is_err());
+    }
+
+    #[test]
+    fn test_custom_themes() {
+        let dir = env::temp_dir().join(format!("ferris-themes-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("ocean.toml"),
+            "[board]\ndark = '#1e3a5f'\nlight = '#c8dcf0'",
+        )
+        .unwrap();
+        fs::write(dir.join("broken.toml"), "[board]\ndark = 'blue'").unwrap();
+        let (themes, errors) = custom_from(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(themes.len(), 1);
+        assert_eq!(themes[0].name, "ocean");
+        assert_eq!(themes[0].board, Some(((30, 58, 95), (200, 220, 240))));
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("broken.toml"));
+    }
+
+    #[test]
+    fn test_built_in_themes() {
+        let mut board = Board::default();
+        set_theme(&mut board, "gruvbox").unwrap();
+        assert_eq!(board.color, board_themes::GRUVBOX.into());
+        set_piece_set(&mut board, "neon").unwrap();
+        assert_eq!(board.white_color, piece_themes::NEON.0);
+        assert!(set_theme(&mut board, "no-such-theme").is_err());
+
+        let text = preview(&board);
+        assert!(text.contains("cotton-candy") && text.contains("hacker"));
+        assert!(text.find("Piece sets:") > text.find("Board themes:"));
+        board.colors = ColorMode::Ansi256;
+        assert!(preview(&board).contains("\x1b[48;5;"));
+        board.colors = ColorMode::Plain;
+        assert!(!preview(&board).contains('\x1b'));
+    }
+}
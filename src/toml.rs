@@ -0,0 +1,177 @@
+/*
+ * File: toml.rs
+ * Purpose: Reading the small TOML files of the commentary and the themes
+ * Date: 18.10.2026
+ * */
+
+/* Only the part of TOML the files of Ferris use is read:
+ *
+ *  # comment
+ *  [section]
+ *  key = "string"            or 'string'
+ *  key = 255
+ *  key = ["a", 'b', 1, [2]]  arrays can go over many lines and end with a comma
+ *
+ * Keys before the first section go to the section "".
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// Keys of every section: table["section"]["key"]
+pub type Table = HashMap<String, HashMap<String, Value>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    /// The string, or the strings of an array, empty strings are left out.
+    pub fn strings(&self) -> Vec<String> {
+        match self {
+            Value::String(text) if !text.is_empty() => vec![text.clone()],
+            Value::Array(values) => values.iter().flat_map(Value::strings).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TomlErr(pub String);
+
+impl Display for TomlErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// Reads "text" or 'text' starting after the quote, returns it and what is left
+fn parse_string(quote: char, text: &str) -> Result<(String, &str), String> {
+    let mut value = String::new();
+    let mut chars = text.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            _ if c == quote => return Ok((value, &text[i + 1..])),
+            '\\' if quote == '"' => match chars.next().map(|(_, c)| c) {
+                Some('n') => value.push('\n'),
+                Some('t') => value.push('\t'),
+                Some(c @ ('"' | '\\')) => value.push(c),
+                _ => return Err("Unknown escape in a string".to_owned()),
+            },
+            '\n' => break,
+            _ => value.push(c),
+        }
+    }
+    Err("String is not closed".to_owned())
+}
+
+// Skips spaces, new lines and comments
+fn skip_blank(mut text: &str) -> &str {
+    loop {
+        text = text.trim_start();
+        match text.strip_prefix('#') {
+            Some(comment) => text = comment.split_once('\n').map_or("", |(_, rest)| rest),
+            None => return text,
+        }
+    }
+}
+
+// Reads a value, errors come with the text where they happened
+fn parse_value(text: &str) -> Result<(Value, &str), (&str, String)> {
+    if let Some(mut rest) = text.strip_prefix('[') {
+        let mut values = Vec::new();
+        rest = skip_blank(rest);
+        while !rest.starts_with(']') {
+            let (value, after) = parse_value(rest)?;
+            values.push(value);
+            rest = skip_blank(after);
+            match rest.strip_prefix(',') {
+                Some(after) => rest = skip_blank(after),
+                None if rest.starts_with(']') => (),
+                None => return Err((rest, "Expected , or ]".to_owned())),
+            }
+        }
+        return Ok((Value::Array(values), &rest[1..]));
+    }
+    match text.chars().next() {
+        Some(quote @ ('"' | '\'')) => {
+            let (string, rest) = parse_string(quote, &text[1..]).map_err(|e| (text, e))?;
+            Ok((Value::String(string), rest))
+        }
+        Some(c) if c.is_ascii_digit() || c == '-' || c == '+' => {
+            let end = text[1..]
+                .find(|c: char| !c.is_ascii_digit() && c != '_')
+                .map_or(text.len(), |end| end + 1);
+            let number = text[..end].replace('_', "");
+            match number.parse() {
+                Ok(number) => Ok((Value::Integer(number), &text[end..])),
+                Err(_) => Err((text, format!("Invalid number {}", number))),
+            }
+        }
+        _ => Err((text, "Expected a string, number or array".to_owned())),
+    }
+}
+
+pub fn parse(text: &str) -> Result<Table, TomlErr> {
+    let mut table = Table::new();
+    let mut section = String::new();
+    let mut rest = skip_blank(text);
+    // Line of the error for the message
+    let error = |rest: &str, message: &str| {
+        let line = text[..text.len() - rest.len()].lines().count().max(1);
+        TomlErr(format!("line {}: {}", line, message))
+    };
+
+    while !rest.is_empty() {
+        if let Some(header) = rest.strip_prefix('[') {
+            let (name, after) = header
+                .split_once(']')
+                .ok_or_else(|| error(rest, "Section is not closed"))?;
+            section = name.trim().to_owned();
+            table.entry(section.clone()).or_default();
+            rest = skip_blank(after);
+            continue;
+        }
+
+        let (key, after) = rest
+            .split_once('=')
+            .filter(|(key, _)| !key.contains('\n'))
+            .ok_or_else(|| error(rest, "Expected key = value"))?;
+        let key = key.trim().to_owned();
+        let (value, after) = parse_value(after.trim_start()).map_err(|(at, e)| error(at, &e))?;
+        table.entry(section.clone()).or_default().insert(key, value);
+        rest = skip_blank(after);
+    }
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let text = "top = 'level'\n[colors] # comment\ndark = \"#102030\"\nlight = [\n\t1, -2, # comment\n\t[3],\n]\n";
+        let table = parse(text).unwrap();
+        assert_eq!(table[""]["top"], Value::String("level".to_owned()));
+        assert_eq!(table["colors"]["dark"].strings(), ["#102030"]);
+        assert_eq!(
+            table["colors"]["light"],
+            Value::Array(vec![
+                Value::Integer(1),
+                Value::Integer(-2),
+                Value::Array(vec![Value::Integer(3)]),
+            ])
+        );
+        assert_eq!(
+            parse("[a]\nkey = [1 2]").unwrap_err().0,
+            "line 2: Expected , or ]"
+        );
+        assert!(parse("key = \"open").is_err());
+        assert!(parse("[open").is_err());
+        assert!(parse("key").is_err());
+    }
+}
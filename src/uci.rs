@@ -0,0 +1,420 @@
+/* File: uci.rs
+ * Purpuse: Speaking the Universal Chess Interface so GUIs can use Ferris as an engine
+ * Date: 18.10.2026
+ * */
+
+/* A GUI talks to the engine line by line over stdin and stdout:
+ *
+ *  GUI                              Ferris
+ *  uci                         ->
+ *                              <-   id name Ferris
+ *                              <-   uciok
+ *  isready                     ->
+ *                              <-   readyok
+ *  position startpos moves e2e4 ->
+ *  go movetime 1000            ->
+ *                              <-   info depth 1 score cp 30 nodes 21 nps 21000 time 1 pv e7e5
+ *                              <-   bestmove e7e5
+ *
+ * Searching happens on another thread so stop, isready and quit can be
+ * answered while it runs.
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::board::{
+    square_name, Board, Move, SearchLimits, SearchResult, TranspositionTable, DEFAULT_HASH_MB,
+    MAX_DEPTH, MAX_HASH_MB,
+};
+use crate::parser::parse_move;
+use std::io::{BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+const MOVES_TO_GO: u64 = 30; // Guess of the moves left when the GUI doesn't tell
+const MOVE_OVERHEAD: u64 = 50; // Milliseconds kept for talking to the GUI
+
+type Output<W> = Arc<Mutex<W>>;
+
+fn send<W: Write>(output: &Output<W>, line: &str) {
+    let mut output = output.lock().unwrap();
+    let _ = writeln!(output, "{}", line);
+    let _ = output.flush();
+}
+
+// e2e4, e7e8q
+pub(crate) fn move_name(board: &Board, current_move: &Move) -> String {
+    let (from, to) = current_move.decode_move();
+    let pawn = board.board[from[1] - 1][from[0] - 1].eq_ignore_ascii_case(&'p');
+    // A promotion without a piece is played as a queen
+    let promotion = match current_move.promotion() {
+        None if pawn && (to[1] == 1 || to[1] == 8) => Some('q'),
+        promotion => promotion,
+    };
+    let mut name = square_name(from) + &square_name(to);
+    name.extend(promotion);
+    name
+}
+
+// Moves of a line only make sense in the position they are played in
+pub(crate) fn pv_names(board: &Board, pv: &[Move]) -> Vec<String> {
+    let mut line = board.clone();
+    let mut names = Vec::with_capacity(pv.len());
+    for current_move in pv {
+        names.push(move_name(&line, current_move));
+        line.make_move(current_move);
+    }
+    names
+}
+
+fn info_line(board: &Board, result: &SearchResult) -> String {
+    let score = match result.mate_in() {
+        Some(moves) => format!("mate {}", moves),
+        None => format!("cp {}", result.score),
+    };
+    format!(
+        "info depth {} score {} nodes {} nps {} time {} pv {}",
+        result.depth,
+        score,
+        result.nodes,
+        result.nps(),
+        result.time.as_millis(),
+        pv_names(board, &result.pv).join(" ")
+    )
+}
+
+struct Engine<W: Write + Send + 'static> {
+    board: Board,
+    output: Output<W>,
+    max_depth: u32,                        // Depth option
+    table: Arc<Mutex<TranspositionTable>>, // Hash option, kept between searches
+    chess960: bool,                        // UCI_Chess960 option, castling is king takes rook
+    stop: Arc<AtomicBool>,
+    search: Option<JoinHandle<()>>,
+}
+
+impl<W: Write + Send + 'static> Engine<W> {
+    fn new(output: Output<W>) -> Self {
+        Engine {
+            board: Board::default(),
+            output,
+            max_depth: MAX_DEPTH,
+            table: Arc::new(Mutex::new(TranspositionTable::default())),
+            chess960: false,
+            stop: Arc::new(AtomicBool::new(false)),
+            search: None,
+        }
+    }
+
+    // Stops the running search and waits for its bestmove
+    fn stop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(search) = self.search.take() {
+            let _ = search.join();
+        }
+    }
+
+    // position [startpos | fen <fen>] [moves <move> ...]
+    fn position(&mut self, args: &str) {
+        let (setup, moves) = match args.split_once("moves") {
+            Some((setup, moves)) => (setup.trim(), moves),
+            None => (args.trim(), ""),
+        };
+        let mut board = Board {
+            chess960: self.chess960,
+            ..Board::default()
+        };
+        if let Some(fen) = setup.strip_prefix("fen") {
+            if let Err(e) = board.set_fen_chess960(fen, self.chess960) {
+                send(&self.output, &format!("info string {}", e));
+                return;
+            }
+        } else if setup != "startpos" {
+            send(&self.output, "info string position needs startpos or fen");
+            return;
+        }
+        for usr in moves.split_whitespace() {
+            let error = match parse_move(&board, usr) {
+                Ok(current_move) if board.legal_moves().contains(&current_move) => {
+                    board.make_move(&current_move);
+                    continue;
+                }
+                Ok(_) => "Illegal move".to_owned(),
+                Err(e) => e.to_string(),
+            };
+            send(&self.output, &format!("info string {}: {}", usr, error));
+            return;
+        }
+        board.set_color(self.board.color);
+        self.board = board;
+    }
+
+    // go [depth <n>] [movetime <ms>] [wtime <ms>] [btime <ms>] [winc <ms>] [binc <ms>] [movestogo <n>] [infinite]
+    fn go(&mut self, args: &str) {
+        self.stop();
+        let mut limits = SearchLimits {
+            depth: self.max_depth,
+            time: None,
+        };
+        let mut infinite = false;
+        let (mut clock, mut increment, mut moves_to_go) = (None, 0, MOVES_TO_GO);
+        let (my_time, my_inc) = if self.board.turn {
+            ("wtime", "winc")
+        } else {
+            ("btime", "binc")
+        };
+
+        let mut words = args.split_whitespace();
+        while let Some(word) = words.next() {
+            let mut number = || {
+                words
+                    .next()
+                    .and_then(|n| n.parse::<i64>().ok())
+                    .unwrap_or(0)
+            };
+            match word {
+                "depth" => limits.depth = (number().max(1) as u32).min(self.max_depth),
+                "movetime" => limits.time = Some(Duration::from_millis(number().max(1) as u64)),
+                "movestogo" => moves_to_go = number().max(1) as u64,
+                "infinite" => infinite = true,
+                _ if word == my_time => clock = Some(number().max(0) as u64),
+                _ if word == my_inc => increment = number().max(0) as u64,
+                _ => (),
+            }
+        }
+        if let Some(clock) = clock {
+            // Spending an equal share of the clock on every move that is left
+            let budget = clock / moves_to_go + increment * 3 / 4;
+            let budget = budget.min(clock.saturating_sub(MOVE_OVERHEAD)).max(10);
+            limits.time = Some(Duration::from_millis(budget));
+        }
+
+        self.stop = Arc::new(AtomicBool::new(false));
+        let (board, output, stop) = (self.board.clone(), self.output.clone(), self.stop.clone());
+        let table = self.table.clone();
+        self.search = Some(thread::spawn(move || {
+            let mut table = table.lock().unwrap();
+            let result = board.search_with(limits, stop.clone(), &mut table, |info| {
+                send(&output, &info_line(&board, info));
+            });
+            // Infinite searches have to wait for stop before answering
+            while infinite && !stop.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(5));
+            }
+            let best = match result.best_move {
+                Some(best_move) => move_name(&board, &best_move),
+                None => "0000".to_owned(),
+            };
+            send(&output, &format!("bestmove {}", best));
+        }));
+    }
+
+    // setoption name <name> value <value>
+    fn set_option(&mut self, args: &str) {
+        let (name, value) = match args.trim().strip_prefix("name") {
+            Some(rest) => match rest.split_once("value") {
+                Some((name, value)) => (name.trim(), value.trim()),
+                None => (rest.trim(), ""),
+            },
+            None => return,
+        };
+        match name.to_ascii_lowercase().as_str() {
+            "depth" => match value.parse::<u32>() {
+                Ok(depth) => self.max_depth = depth.clamp(1, MAX_DEPTH),
+                Err(_) => send(
+                    &self.output,
+                    &format!("info string invalid depth {}", value),
+                ),
+            },
+            "hash" => match value.parse::<usize>() {
+                Ok(megabytes) => {
+                    self.stop();
+                    *self.table.lock().unwrap() = TranspositionTable::new(megabytes);
+                }
+                Err(_) => send(
+                    &self.output,
+                    &format!("info string invalid hash size {}", value),
+                ),
+            },
+            "uci_chess960" => self.chess960 = value.eq_ignore_ascii_case("true"),
+            _ => send(
+                &self.output,
+                &format!("info string unknown option {}", name),
+            ),
+        }
+    }
+
+    // Returns false when the engine should quit
+    fn execute(&mut self, line: &str) -> bool {
+        let line = line.trim();
+        let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+        match command {
+            "uci" => {
+                send(&self.output, "id name Ferris");
+                send(&self.output, "id author KoBruhh");
+                send(
+                    &self.output,
+                    &format!(
+                        "option name Depth type spin default {} min 1 max {}",
+                        MAX_DEPTH, MAX_DEPTH
+                    ),
+                );
+                send(
+                    &self.output,
+                    &format!(
+                        "option name Hash type spin default {} min 1 max {}",
+                        DEFAULT_HASH_MB, MAX_HASH_MB
+                    ),
+                );
+                send(
+                    &self.output,
+                    "option name UCI_Chess960 type check default false",
+                );
+                send(&self.output, "uciok");
+            }
+            "isready" => send(&self.output, "readyok"),
+            "ucinewgame" => {
+                self.stop();
+                self.board = Board::default();
+                self.table.lock().unwrap().clear();
+            }
+            "position" => {
+                self.stop();
+                self.position(args);
+            }
+            "go" => self.go(args),
+            "stop" => self.stop(),
+            "setoption" => self.set_option(args),
+            "quit" => {
+                self.stop();
+                return false;
+            }
+            "" => (),
+            _ => send(
+                &self.output,
+                &format!("info string unknown command {}", command),
+            ),
+        }
+        true
+    }
+}
+
+/// Answers UCI commands from the input until quit or the end of the input.
+pub fn run<W: Write + Send + 'static>(input: impl BufRead, output: Output<W>) {
+    let mut engine = Engine::new(output);
+    for line in input.lines() {
+        match line {
+            Ok(line) if engine.execute(&line) => (),
+            _ => break,
+        }
+    }
+    // Whatever is still searching answers before leaving
+    if let Some(search) = engine.search.take() {
+        let _ = search.join();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script(commands: &str) -> Vec<String> {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        run(commands.as_bytes(), output.clone());
+        let bytes = output.lock().unwrap().clone();
+        String::from_utf8(bytes)
+            .unwrap()
+            .lines()
+            .map(str::to_owned)
+            .collect()
+    }
+
+    #[test]
+    fn test_handshake() {
+        let lines = script("uci\nisready\n");
+        assert_eq!(lines[0], "id name Ferris");
+        assert_eq!(lines[lines.len() - 2], "uciok");
+        assert_eq!(lines[lines.len() - 1], "readyok");
+    }
+
+    #[test]
+    fn test_go_depth() {
+        let lines = script("position startpos moves e2e4 e7e5\ngo depth 3\n");
+        let infos: Vec<&String> = lines
+            .iter()
+            .filter(|l| l.starts_with("info depth"))
+            .collect();
+        assert_eq!(infos.len(), 3);
+        assert!(infos[2].starts_with("info depth 3 score cp "));
+        assert!(
+            infos[2].contains(" nodes ") && infos[2].contains(" nps ") && infos[2].contains(" pv ")
+        );
+        assert!(lines.last().unwrap().starts_with("bestmove "));
+    }
+
+    #[test]
+    fn test_mate_and_promotion() {
+        let lines = script("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\ngo depth 2\n");
+        assert!(lines.iter().any(|l| l.contains("score mate 1")));
+        assert_eq!(lines.last().unwrap(), "bestmove a1a8");
+
+        let lines = script("position fen 8/P6k/8/8/8/8/8/K7 w - - 0 1\ngo depth 3\n");
+        assert_eq!(lines.last().unwrap(), "bestmove a7a8q");
+
+        // Underpromotions from the GUI are played, a king is not a promotion
+        let lines = script("position fen 8/P6k/8/8/8/8/8/K7 w - - 0 1 moves a7a8n h7g6\n");
+        assert!(lines.is_empty());
+        let lines = script("position fen 8/P6k/8/8/8/8/8/K7 w - - 0 1 moves a7a8k\n");
+        assert!(lines[0].starts_with("info string a7a8k: "));
+    }
+
+    #[test]
+    fn test_infinite_and_stop() {
+        let lines = script("position startpos\ngo infinite\nisready\nstop\nquit\ngo depth 1\n");
+        assert!(lines.contains(&"readyok".to_owned()));
+        assert_eq!(
+            lines.iter().filter(|l| l.starts_with("bestmove")).count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_clock_and_options() {
+        let lines =
+            script("setoption name Depth value 2\nposition startpos\ngo wtime 1000 btime 1000\n");
+        assert!(lines.iter().all(|l| !l.starts_with("info depth 3")));
+        assert!(lines.last().unwrap().starts_with("bestmove "));
+
+        let lines = script("position startpos moves e2e5\nsetoption name Threads value 2\n");
+        assert!(lines[0].starts_with("info string e2e5"));
+        assert_eq!(lines[1], "info string unknown option Threads");
+
+        let lines = script("uci\nsetoption name Hash value 1\nsetoption name Hash value x\n");
+        assert!(lines
+            .iter()
+            .any(|l| l.starts_with("option name Hash type spin")));
+        assert_eq!(lines.last().unwrap(), "info string invalid hash size x");
+    }
+
+    #[test]
+    fn test_chess960_castling() {
+        let opening = "position startpos moves e2e4 e7e5 g1f3 b8c6 f1c4 g8f6";
+        let lines = script(&format!("{} e1g1\n", opening));
+        assert!(lines.is_empty());
+
+        // With UCI_Chess960 the king takes its own rook
+        let chess960 = "setoption name UCI_Chess960 value true\n";
+        let lines = script(&format!("{}{} e1g1\n", chess960, opening));
+        assert!(lines[0].starts_with("info string e1g1"));
+        let lines = script(&format!("{}{} e1h1\ngo depth 1\n", chess960, opening));
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with("bestmove "));
+
+        let fen = "rk2r3/8/8/8/8/8/8/RK2R3 w KQkq - 0 1";
+        let lines = script(&format!(
+            "{}position fen {} moves b1a1 b8a8\n",
+            chess960, fen
+        ));
+        assert!(lines.is_empty());
+    }
+}
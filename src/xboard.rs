@@ -0,0 +1,340 @@
+/* File: xboard.rs
+ * Purpuse: Speaking the Chess Engine Communication Protocol (XBoard/WinBoard)
+ * Date: 18.10.2026
+ * */
+
+/* Unlike UCI, an XBoard GUI tells the engine which side it plays and
+ * sends the opponent's moves one by one:
+ *
+ *  GUI                              Ferris
+ *  xboard                      ->
+ *  protover 2                  ->
+ *                              <-   feature ping=1 setboard=1 usermove=1 ... done=1
+ *  new                         ->   (engine plays black)
+ *  level 40 5 0                ->   (40 moves in 5 minutes)
+ *  usermove e2e4               ->
+ *                              <-   move e7e5
+ *  force                       ->   (engine stops playing, moves are only recorded)
+ *  go                          ->   (engine plays the side to move from now on)
+ *
+ * Times from the GUI are in centiseconds. In Chess960 (variant fischerandom)
+ * castling moves are sent as O-O and O-O-O both ways.
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::board::{
+    Board, SearchLimits, SearchResult, TranspositionTable, DEFAULT_HASH_MB, MAX_DEPTH,
+};
+use crate::commands::undo_moves;
+use crate::parser::parse_move;
+use crate::uci::{move_name, pv_names};
+use std::io::{BufRead, Write};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+
+const MOVES_TO_GO: u32 = 30; // Guess of the moves left in sudden death games
+const FEATURES: &str = "feature ping=1 setboard=1 usermove=1 time=1 draw=0 sigint=0 \
+                        sigterm=0 reuse=1 analyze=0 colors=0 memory=1 \
+                        variants=\"normal,fischerandom\" myname=\"Ferris\" done=1";
+
+struct Engine<W: Write> {
+    board: Board,
+    output: W,
+    engine_side: Option<bool>, // None in force mode, Some(true) if the engine plays white
+    post: bool,                // Sending thinking output
+    max_depth: u32,            // sd
+    move_time: Option<u64>,    // st, centiseconds for every move
+    moves_per_session: u32,    // level, 0 for the whole game
+    increment: u64,            // level, centiseconds
+    clock: Option<u64>,        // time, centiseconds left on the engine's clock
+    table: TranspositionTable, // memory, kept between moves
+    chess960: bool,            // variant fischerandom, until the next new
+}
+
+impl<W: Write> Engine<W> {
+    fn new(output: W) -> Self {
+        Engine {
+            board: Board::default(),
+            output,
+            engine_side: Some(false),
+            post: false,
+            max_depth: MAX_DEPTH,
+            move_time: None,
+            moves_per_session: 0,
+            increment: 0,
+            clock: None,
+            table: TranspositionTable::default(),
+            chess960: false,
+        }
+    }
+
+    fn send(&mut self, line: &str) {
+        let _ = writeln!(self.output, "{}", line);
+        let _ = self.output.flush();
+    }
+
+    fn limits(&self) -> SearchLimits {
+        let centiseconds = match (self.move_time, self.clock) {
+            (Some(move_time), _) => Some(move_time),
+            (None, Some(clock)) => {
+                // Moves left until the next time control
+                let played = self.board.fullmove_number.saturating_sub(1);
+                let moves_to_go = match self.moves_per_session {
+                    0 => MOVES_TO_GO,
+                    session => session - played % session,
+                };
+                let budget = clock / moves_to_go as u64 + self.increment * 3 / 4;
+                Some(budget.min(clock.saturating_sub(5)).max(1))
+            }
+            (None, None) => None,
+        };
+        SearchLimits {
+            depth: self.max_depth,
+            time: Some(Duration::from_millis(centiseconds.unwrap_or(500) * 10)),
+        }
+    }
+
+    // Tells the result to the GUI if the game is over, returns true if it is.
+    // Claimable draws are always claimed, the search scores them as draws anyway
+    fn check_result(&mut self) -> bool {
+        match self.board.result().or(self.board.claimable_draw()) {
+            Some(result) => {
+                self.send(&format!("{} {{{}}}", result.score(), result));
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Thinks and plays a move if it is the engine's turn
+    fn think(&mut self) {
+        if self.engine_side != Some(self.board.turn) || self.check_result() {
+            return;
+        }
+        let (board, post) = (self.board.clone(), self.post);
+        let mut thinking = Vec::new();
+        let stop = Arc::new(AtomicBool::new(false));
+        let limits = self.limits();
+        let result = board.search_with(limits, stop, &mut self.table, |info: &SearchResult| {
+            if post {
+                // ply score time nodes pv
+                thinking.push(format!(
+                    "{} {} {} {} {}",
+                    info.depth,
+                    info.score,
+                    info.time.as_millis() / 10,
+                    info.nodes,
+                    pv_names(&board, &info.pv).join(" ")
+                ));
+            }
+        });
+        for line in thinking {
+            self.send(&line);
+        }
+
+        let best_move = match result.best_move {
+            Some(best_move) => best_move,
+            None => return,
+        };
+        let name = match self.board.castling_side(&best_move) {
+            Some(true) if self.board.chess960 => "O-O".to_owned(),
+            Some(false) if self.board.chess960 => "O-O-O".to_owned(),
+            _ => move_name(&self.board, &best_move),
+        };
+        let _ = self.board.move_piece(best_move);
+        self.send(&format!("move {}", name));
+        self.check_result();
+    }
+
+    fn user_move(&mut self, usr: &str) {
+        let played = parse_move(&self.board, usr)
+            .ok()
+            .filter(|current_move| self.board.legal_moves().contains(current_move))
+            .map(|current_move| self.board.make_move(&current_move));
+        match played {
+            Some(_) if !self.check_result() => self.think(),
+            Some(_) => (),
+            None => self.send(&format!("Illegal move: {}", usr)),
+        }
+    }
+
+    // level <moves per session> <base minutes[:seconds]> <increment seconds>
+    fn level(&mut self, args: &str) {
+        let args: Vec<&str> = args.split_whitespace().collect();
+        if let [session, _, increment] = args[..] {
+            self.moves_per_session = session.parse().unwrap_or(0);
+            self.increment = increment
+                .parse::<f64>()
+                .map_or(0, |secs| (secs * 100.0) as u64);
+            self.move_time = None;
+        }
+    }
+
+    // Returns false when the engine should quit
+    fn execute(&mut self, line: &str) -> bool {
+        let line = line.trim();
+        let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+        let number = args.trim().parse::<u64>().ok();
+        match command {
+            "xboard" | "accepted" | "rejected" | "random" | "hard" | "easy" | "computer"
+            | "otim" | "name" | "" => (),
+            "protover" => self.send(FEATURES),
+            "new" => {
+                let color = self.board.color;
+                self.board = Board::default();
+                self.board.set_color(color);
+                self.engine_side = Some(false);
+                self.max_depth = MAX_DEPTH;
+                self.move_time = None;
+                self.table.clear();
+                self.chess960 = false;
+            }
+            "force" | "result" => self.engine_side = None,
+            "go" => {
+                self.engine_side = Some(self.board.turn);
+                self.think();
+            }
+            "playother" => self.engine_side = Some(!self.board.turn),
+            // The GUI sends the position with setboard after the variant
+            "variant" => {
+                self.chess960 = args.trim() == "fischerandom";
+                self.board.chess960 = self.chess960;
+            }
+            "usermove" => self.user_move(args.trim()),
+            "setboard" => {
+                if self.board.set_fen_chess960(args, self.chess960).is_err() {
+                    self.send("tellusererror Illegal position");
+                }
+            }
+            // Same as the undo command of the game, remove takes back a move of both sides
+            "undo" => {
+                undo_moves(&mut self.board, 1);
+            }
+            "remove" => {
+                undo_moves(&mut self.board, 2);
+            }
+            "level" => self.level(args),
+            "st" => self.move_time = number.map(|secs| secs * 100),
+            "sd" => self.max_depth = number.map_or(MAX_DEPTH, |d| (d as u32).clamp(1, MAX_DEPTH)),
+            "time" => self.clock = number,
+            "memory" => {
+                let megabytes = number.map_or(DEFAULT_HASH_MB, |mb| mb as usize);
+                self.table = TranspositionTable::new(megabytes);
+            }
+            "ping" => self.send(&format!("pong {}", args.trim())),
+            "post" => self.post = true,
+            "nopost" => self.post = false,
+            "quit" => return false,
+            // Moves can come without usermove from older GUIs
+            _ if parse_move(&self.board, line).is_ok() => self.user_move(line),
+            _ => self.send(&format!("Error (unknown command): {}", line)),
+        }
+        true
+    }
+}
+
+/// Answers XBoard commands from the input until quit or the end of the input.
+pub fn run(input: impl BufRead, output: impl Write) {
+    let mut engine = Engine::new(output);
+    for line in input.lines() {
+        match line {
+            Ok(line) if engine.execute(&line) => (),
+            _ => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script(commands: &str) -> Vec<String> {
+        let mut output = Vec::new();
+        run(commands.as_bytes(), &mut output);
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(str::to_owned)
+            .collect()
+    }
+
+    #[test]
+    fn test_features() {
+        let lines = script("xboard\nprotover 2\nping 7\n");
+        assert!(lines[0].starts_with("feature ") && lines[0].contains("usermove=1"));
+        assert!(lines[0].ends_with("done=1"));
+        assert_eq!(lines[1], "pong 7");
+    }
+
+    #[test]
+    fn test_engine_answers() {
+        let lines = script("xboard\nnew\nsd 2\nusermove e2e4\n");
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].starts_with("move "));
+
+        let lines = script("new\nsd 1\nusermove e2e5\nforce\nusermove e2e4\nusermove e7e5\n");
+        assert_eq!(lines, vec!["Illegal move: e2e5"]);
+    }
+
+    #[test]
+    fn test_go_and_thinking() {
+        let lines = script("new\nsd 2\npost\ngo\n");
+        assert!(lines[0].starts_with("1 "));
+        assert!(lines[1].starts_with("2 "));
+        assert!(lines[2].starts_with("move "));
+    }
+
+    #[test]
+    fn test_setboard_undo_and_mate() {
+        let lines = script(
+            "force\nsetboard 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\nusermove a1a2\nremove\nsd 2\ngo\n",
+        );
+        assert_eq!(lines, vec!["move a1a8", "1-0 {Checkmate! White wins}"]);
+
+        let lines = script("setboard 8/8/8/8 w - - 0 1\n");
+        assert_eq!(lines, vec!["tellusererror Illegal position"]);
+    }
+
+    #[test]
+    fn test_time_controls() {
+        let mut engine = Engine::new(Vec::new());
+        engine.execute("level 40 5 0");
+        engine.execute("time 30000");
+        assert_eq!(engine.limits().time, Some(Duration::from_millis(7500)));
+        engine.execute("st 2");
+        assert_eq!(engine.limits().time, Some(Duration::from_secs(2)));
+        engine.execute("level 0 1 2");
+        engine.execute("time 6000");
+        assert_eq!(engine.limits().time, Some(Duration::from_millis(3500)));
+    }
+
+    #[test]
+    fn test_fischerandom() {
+        let lines = script(
+            "new\nvariant fischerandom\nforce\nsetboard 4k3/8/8/8/8/8/8/R5KR b HA - 0 1\n\
+             usermove e8e7\nsd 1\ngo\n",
+        );
+        assert!(lines[0].starts_with("move "));
+
+        let mut engine = Engine::new(Vec::new());
+        engine.execute("variant fischerandom");
+        engine.execute("force");
+        engine.execute("setboard 4k3/8/8/8/8/8/8/R5KR w HA - 0 1");
+        engine.execute("usermove O-O");
+        assert_eq!(engine.board.to_fen(), "4k3/8/8/8/8/8/8/R4RK1 b - - 1 1");
+
+        // A new game without the variant castles normally
+        engine.execute("new");
+        engine.execute("force");
+        engine.execute("setboard 4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1");
+        engine.execute("usermove e1g1");
+        assert_eq!(engine.board.to_fen(), "4k3/8/8/8/8/8/8/R4RK1 b - - 1 1");
+    }
+
+    #[test]
+    fn test_memory() {
+        let mut engine = Engine::new(Vec::new());
+        engine.execute("memory 2");
+        assert_eq!(engine.table.megabytes(), 2);
+    }
+}
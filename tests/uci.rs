@@ -0,0 +1,52 @@
+/* File: uci.rs
+ * Purpuse: Running the binary as a UCI engine, stdout must only carry the protocol
+ * Date: 18.10.2026
+ * */
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// First words of everything an engine may send to the GUI
+const ANSWERS: [&str; 8] = [
+    "id",
+    "uciok",
+    "readyok",
+    "bestmove",
+    "copyprotection",
+    "registration",
+    "info",
+    "option",
+];
+
+fn ferris(commands: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ferris"))
+        .arg("--uci")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(commands.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_only_protocol_on_stdout() {
+    let stdout = ferris(
+        "uci\nposition startpos moves a1h8\nisready\n\
+         position startpos moves e2e5\nposition startpos moves e2e4 e7e5 e1e3\n\
+         position startpos moves e2e4\ngo depth 2\nquit\n",
+    );
+    assert!(stdout.contains("info string a1h8: "));
+    assert!(stdout.contains("readyok"));
+    for line in stdout.lines() {
+        let first = line.split_whitespace().next().unwrap_or("");
+        assert!(ANSWERS.contains(&first), "not UCI: {:?}", line);
+    }
+}
@@ -0,0 +1,55 @@
+/* File: xboard.rs
+ * Purpuse: Running the binary as an XBoard engine, stdout must only carry the protocol
+ * Date: 18.10.2026
+ * */
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// First words of everything an engine may send to the GUI,
+// thinking lines and results start with a number
+const ANSWERS: [&str; 10] = [
+    "feature",
+    "pong",
+    "move",
+    "Illegal",
+    "Error",
+    "tellusererror",
+    "telluser",
+    "resign",
+    "offer",
+    "#",
+];
+
+fn ferris(commands: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ferris"))
+        .arg("--xboard")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(commands.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_only_protocol_on_stdout() {
+    let stdout = ferris(
+        "xboard\nprotover 2\nnew\nsd 2\npost\nusermove e2e5\nusermove a1h8\n\
+         e2e5\nusermove e2e4\nquit\n",
+    );
+    assert!(stdout.contains("Illegal move: e2e5"));
+    assert!(stdout.contains("\nmove "));
+    for line in stdout.lines() {
+        let first = line.split_whitespace().next().unwrap_or("");
+        let number = first.starts_with(|c: char| c.is_ascii_digit());
+        assert!(number || ANSWERS.contains(&first), "not XBoard: {:?}", line);
+    }
+}